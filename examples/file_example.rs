@@ -1,6 +1,7 @@
 extern crate cvlcore;
 use cvlcore::api::capture::*;
 use cvlcore::api::chain::*;
+use cvlcore::api::state::ChainOutput;
 use cvlcore::errors::*;
 use cvlcore::ui::window::*;
 
@@ -13,7 +14,7 @@ fn main() -> CaptureResult {
     let window = MainWindow::new(window_name);
     window.create_window();
 
-    let mut vcap = CvlCapture::default();
+    let mut vcap = CvlCapture::new()?;
     vcap.open_stream(file_path_arg.as_str(), StreamSource::VideoFile)?;
     processing_stream(&mut vcap, &window);
 
@@ -32,13 +33,14 @@ fn processing_stream(vcap: &mut CvlCapture, window: &MainWindow) {
             .reduce_abs()
             .vibrating();
 
-        let chain_result = precessing_result.get_result();
-        if chain_result.is_err() {
-            println!("{}", chain_result.err().unwrap());
-            continue;
-        }
-
-        let cvl_mat = chain_result.unwrap();
+        let cvl_mat = match precessing_result.output() {
+            ChainOutput::Ready(cvl_mat) => cvl_mat,
+            ChainOutput::WarmingUp { .. } | ChainOutput::NoFace => continue,
+            ChainOutput::Failed(err) => {
+                println!("{}", err);
+                continue;
+            }
+        };
         window.show_frame(&cvl_mat);
         match window.wait_event() {
             WindowSignals::KeepProcessing => {}
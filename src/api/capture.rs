@@ -17,8 +17,22 @@ pub struct CvlCapture {
 }
 
 impl CvlCapture {
-    pub fn new() -> Self {
-        CvlCapture::default()
+    /// Creates new capture which is not opened yet.
+    ///
+    /// ## Errors:
+    /// Returns [`CreateCapture`](CaptureError::CreateCapture) if failed while trying to create
+    /// video capture object.
+    pub fn new() -> Result<Self, CaptureError> {
+        match VideoCapture::default() {
+            Ok(capture) => Ok(CvlCapture {
+                capture,
+                api: CAP_ANY,
+            }),
+            Err(err) => {
+                let msg = format!("Failed create video capture: {}", err);
+                Err(CaptureError::CreateCapture(msg))
+            }
+        }
     }
 
     pub fn open_stream(&mut self, address: &str, source_type: StreamSource) -> CaptureResult {
@@ -43,9 +57,9 @@ impl CvlCapture {
 
     pub fn read_frame(&mut self) -> ReadFrameResult {
         let mut frame = Mat::default();
-        match self.capture.read(&mut frame).unwrap() {
-            false => Err(ReadFrameError::NextFrameError),
-            true => Ok(CvlMat::from(frame)),
+        match self.capture.read(&mut frame) {
+            Ok(true) => Ok(CvlMat::from(frame)),
+            _ => Err(ReadFrameError::NextFrameError),
         }
     }
 
@@ -58,11 +72,11 @@ impl CvlCapture {
 }
 
 impl Default for CvlCapture {
+    /// Creates new capture which is not opened yet, see [`CvlCapture::new`].
+    ///
+    /// ## Panics:
+    /// Panics if failed while trying to create video capture object.
     fn default() -> Self {
-        let capture = VideoCapture::default().unwrap();
-        CvlCapture {
-            capture,
-            api: CAP_ANY,
-        }
+        CvlCapture::new().expect("Failed create video capture")
     }
 }
@@ -1,9 +1,32 @@
+use crate::api::events::{ChainEvent, ChainObserver};
+use crate::api::face::{FaceTracker, TrackingState};
+use crate::api::profile::{AllocationStats, ProfileReport, Profiler};
+use crate::api::snapshot::{ChainSnapshot, MatSnapshot, RoiSnapshot, SNAPSHOT_VERSION};
+use crate::api::stages::{ChainStage, Morphology, MorphologyStage, ThresholdStage};
+use crate::api::state::{ChainOutput, ChainState};
 use crate::core::bounds::ColorBounds;
+use crate::core::denoise::Denoise;
+use crate::core::deque::CvlMatDeque;
+use crate::core::edge::EdgeDetector;
+use crate::core::flicker::FlickerCompensator;
+use crate::core::illumination::Illumination;
 use crate::core::mat::CvlMat;
+use crate::core::motion::{Motion, Stabilization};
+use crate::core::roi::RegionOfInterest;
+use crate::core::scale::{Resize, ResolutionScale};
+use crate::core::zones::{Zone, ZoneLayout, ZoneStatistic};
 use crate::errors::*;
+use crate::ui::render::*;
 use crate::*;
+use opencv::core::{count_non_zero, Rect, CV_32S};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::rc::Rc;
+use std::time::Instant;
 
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ProcessingSettings {
     pub frames_count: usize,
     pub neighbours: i32,
@@ -12,7 +35,27 @@ pub struct ProcessingSettings {
     pub canny_ksize: i32,
     pub canny_sigma: f64,
     pub canny_is_l2: bool,
+    pub edge_detector: EdgeDetector,
+    pub edge_threshold: f64,
     pub normalization: f32,
+    pub amplitude_decay: f64,
+    pub colormap: Colormap,
+    pub colorbar: bool,
+    pub overlay_opacity: f64,
+    pub overlay_grayscale: bool,
+    /// The region of interest is not (de)serialized with other settings, because mask images
+    /// can't be stored in text configs. It is set by [`PipelineConfig`](crate::api::config::PipelineConfig)
+    /// `roi` description or restored by [`ChainSnapshot`] which stores masks as frame data.
+    #[serde(skip)]
+    pub roi: RegionOfInterest,
+    pub stabilization: Stabilization,
+    pub illumination: Illumination,
+    pub flicker_window: usize,
+    pub flicker_threshold: f64,
+    pub denoise: Denoise,
+    pub temporal_median: usize,
+    pub resize: Resize,
+    pub reference_width: i32,
 }
 
 impl Default for ProcessingSettings {
@@ -25,16 +68,262 @@ impl Default for ProcessingSettings {
             canny_ksize: 3,
             canny_sigma: 0.05,
             canny_is_l2: true,
+            edge_detector: EdgeDetector::default(),
+            edge_threshold: 0.0,
             normalization: 10.0,
+            amplitude_decay: 0.8,
+            colormap: Colormap::default(),
+            colorbar: false,
+            overlay_opacity: 0.6,
+            overlay_grayscale: true,
+            roi: RegionOfInterest::default(),
+            stabilization: Stabilization::default(),
+            illumination: Illumination::default(),
+            flicker_window: 0,
+            flicker_threshold: 0.01,
+            denoise: Denoise::default(),
+            temporal_median: 0,
+            resize: Resize::default(),
+            reference_width: 0,
         }
     }
 }
 
+impl ProcessingSettings {
+    pub fn builder() -> ProcessingSettingsBuilder {
+        ProcessingSettingsBuilder::default()
+    }
+
+    /// This method checks constraints of all settings values.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(())` if settings are valid, otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`SettingsError`] describing the first violated constraint.
+    pub fn validate(&self) -> SettingsResult<()> {
+        check_positive("frames_count", self.frames_count as f64)?;
+        check_positive("neighbours", self.neighbours as f64)?;
+        check_positive("window_size", self.window_size as f64)?;
+        check_positive("normalization", self.normalization as f64)?;
+        check_range("canny_sigma", self.canny_sigma, 0f64, 1f64)?;
+        check_range("amplitude_decay", self.amplitude_decay, 0f64, 1f64)?;
+        check_range("overlay_opacity", self.overlay_opacity, 0f64, 1f64)?;
+        check_range("edge_threshold", self.edge_threshold, 0f64, 255f64)?;
+        check_range("flicker_threshold", self.flicker_threshold, 0f64, 1f64)?;
+        check_range(
+            "reference_width",
+            self.reference_width as f64,
+            0f64,
+            f64::MAX,
+        )?;
+
+        if ![3, 5, 7].contains(&self.canny_ksize) {
+            return Err(SettingsError::InvalidCannyKsize);
+        }
+
+        if let Colormap::Custom(colors) = &self.colormap {
+            if colors.len() != 256 {
+                return Err(SettingsError::InvalidColormap);
+            }
+        }
+
+        match self.edge_detector {
+            EdgeDetector::Canny { low, high } => {
+                check_range("edge_detector", low, 0f64, f64::MAX)?;
+                if low > high {
+                    return Err(SettingsError::Unsorted("edge_detector"));
+                }
+            }
+            EdgeDetector::Sobel { ksize } | EdgeDetector::Laplacian { ksize } => {
+                check_kernel_size("edge_detector", ksize)?
+            }
+            _ => {}
+        }
+
+        match self.denoise {
+            Denoise::Gaussian { ksize, sigma } => {
+                check_kernel_size("denoise", ksize)?;
+                check_range("denoise", sigma, 0f64, f64::MAX)?;
+            }
+            Denoise::Median { ksize } if ksize < 3 => {
+                return Err(SettingsError::InvalidKernelSize("denoise"));
+            }
+            Denoise::Median { ksize } => check_kernel_size("denoise", ksize)?,
+            _ => {}
+        }
+
+        match self.illumination {
+            Illumination::Clahe {
+                clip_limit,
+                tile_size,
+            } => {
+                check_positive("illumination", clip_limit)?;
+                check_positive("illumination", tile_size as f64)?;
+            }
+            Illumination::MeanStd { std, .. } => check_positive("illumination", std)?,
+            _ => {}
+        }
+
+        match self.resize {
+            Resize::Width(width) => check_positive("resize", width as f64),
+            Resize::Scale(factor) => check_positive("resize", factor),
+            Resize::None => Ok(()),
+        }
+    }
+}
+
+fn check_positive(key: &'static str, value: f64) -> SettingsResult<()> {
+    match value > 0f64 {
+        true => Ok(()),
+        false => Err(SettingsError::NotPositive(key)),
+    }
+}
+
+fn check_range(key: &'static str, value: f64, min: f64, max: f64) -> SettingsResult<()> {
+    match (min..=max).contains(&value) {
+        true => Ok(()),
+        false => Err(SettingsError::OutOfRange { key, min, max }),
+    }
+}
+
+fn check_kernel_size(key: &'static str, ksize: i32) -> SettingsResult<()> {
+    match ksize > 0 && ksize % 2 == 1 {
+        true => Ok(()),
+        false => Err(SettingsError::InvalidKernelSize(key)),
+    }
+}
+
+/// A builder of [`ProcessingSettings`] which starts from default values and validates
+/// result settings.
+#[derive(Default)]
+pub struct ProcessingSettingsBuilder {
+    settings: ProcessingSettings,
+}
+
+impl ProcessingSettingsBuilder {
+    pub fn frames_count(mut self, value: usize) -> Self {
+        self.settings.frames_count = value;
+        self
+    }
+
+    pub fn neighbours(mut self, value: i32) -> Self {
+        self.settings.neighbours = value;
+        self
+    }
+
+    pub fn window_size(mut self, value: i32) -> Self {
+        self.settings.window_size = value;
+        self
+    }
+
+    pub fn is_reduced_abs(mut self, value: bool) -> Self {
+        self.settings.is_reduced_abs = value;
+        self
+    }
+
+    pub fn canny(mut self, ksize: i32, sigma: f64, is_l2: bool) -> Self {
+        self.settings.canny_ksize = ksize;
+        self.settings.canny_sigma = sigma;
+        self.settings.canny_is_l2 = is_l2;
+        self
+    }
+
+    pub fn edge_detector(mut self, detector: EdgeDetector, threshold: f64) -> Self {
+        self.settings.edge_detector = detector;
+        self.settings.edge_threshold = threshold;
+        self
+    }
+
+    pub fn normalization(mut self, value: f32) -> Self {
+        self.settings.normalization = value;
+        self
+    }
+
+    pub fn amplitude_decay(mut self, value: f64) -> Self {
+        self.settings.amplitude_decay = value;
+        self
+    }
+
+    pub fn colormap(mut self, colormap: Colormap, colorbar: bool) -> Self {
+        self.settings.colormap = colormap;
+        self.settings.colorbar = colorbar;
+        self
+    }
+
+    pub fn overlay(mut self, opacity: f64, grayscale: bool) -> Self {
+        self.settings.overlay_opacity = opacity;
+        self.settings.overlay_grayscale = grayscale;
+        self
+    }
+
+    pub fn roi(mut self, roi: RegionOfInterest) -> Self {
+        self.settings.roi = roi;
+        self
+    }
+
+    pub fn stabilization(mut self, value: Stabilization) -> Self {
+        self.settings.stabilization = value;
+        self
+    }
+
+    pub fn illumination(mut self, value: Illumination) -> Self {
+        self.settings.illumination = value;
+        self
+    }
+
+    pub fn flicker(mut self, window: usize, threshold: f64) -> Self {
+        self.settings.flicker_window = window;
+        self.settings.flicker_threshold = threshold;
+        self
+    }
+
+    pub fn denoise(mut self, denoise: Denoise, temporal_median: usize) -> Self {
+        self.settings.denoise = denoise;
+        self.settings.temporal_median = temporal_median;
+        self
+    }
+
+    pub fn resize(mut self, resize: Resize, reference_width: i32) -> Self {
+        self.settings.resize = resize;
+        self.settings.reference_width = reference_width;
+        self
+    }
+
+    /// This method returns settings if all values are valid.
+    ///
+    /// ## Errors:
+    /// Returns [`SettingsError`] describing the first violated constraint.
+    pub fn build(self) -> SettingsResult<ProcessingSettings> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
+
 pub struct ChainProcessing {
     result: ProcessingResult,
-    frames: Vec<Rc<CvlMat>>,
-    statistics: Vec<Statistic>,
+    source: CvlMat,
+    frames: CvlMatDeque<Rc<CvlMat>>,
+    statistics: CvlMatDeque<Statistic>,
     dispersion: Option<Dispersion>,
+    amplitude: Option<CvlMat>,
+    zones: Option<ZoneLayout>,
+    zones_statistic: HashMap<String, ZoneStatistic>,
+    face_tracker: Option<FaceTracker>,
+    stabilization_reference: Option<CvlMat>,
+    motions: CvlMatDeque<Motion>,
+    flicker: FlickerCompensator,
+    flicker_flags: CvlMatDeque<bool>,
+    denoise_history: CvlMatDeque<Rc<CvlMat>>,
+    frame_index: usize,
+    state: ChainState,
+    failures: usize,
+    events: Vec<ChainEvent>,
+    observers: Vec<(usize, Box<dyn ChainObserver>)>,
+    next_observer_id: usize,
+    profiler: Option<Profiler>,
+    taps: HashMap<String, ProcessingResult>,
+    pending_settings: Option<ProcessingSettings>,
     bounds: ColorBounds,
     settings: ProcessingSettings,
 }
@@ -42,154 +331,635 @@ pub struct ChainProcessing {
 impl Default for ChainProcessing {
     fn default() -> Self {
         let proc_settings = ProcessingSettings::default();
-        ChainProcessing::new(proc_settings)
+        ChainProcessing::from_settings(proc_settings)
     }
 }
 
 impl ChainProcessing {
-    pub fn new(proc_settings: ProcessingSettings) -> Self {
+    /// This method returns new chain with passed settings.
+    ///
+    /// ## Errors:
+    /// Returns [`SettingsError`] if passed settings are invalid (see [`ProcessingSettings::validate`]).
+    pub fn new(proc_settings: ProcessingSettings) -> SettingsResult<Self> {
+        proc_settings.validate()?;
+        Ok(ChainProcessing::from_settings(proc_settings))
+    }
+
+    fn from_settings(proc_settings: ProcessingSettings) -> Self {
+        let (frames_count, temporal_median) =
+            (proc_settings.frames_count, proc_settings.temporal_median);
+        let flicker = FlickerCompensator::new(
+            proc_settings.flicker_window,
+            proc_settings.flicker_threshold,
+        );
+
         ChainProcessing {
-            statistics: Vec::with_capacity(proc_settings.frames_count),
-            frames: Vec::with_capacity(proc_settings.frames_count),
+            statistics: CvlMatDeque::new(frames_count),
+            frames: CvlMatDeque::new(frames_count),
             bounds: ColorBounds::default(),
             result: Ok(CvlMat::default()),
+            source: CvlMat::default(),
             settings: proc_settings,
             dispersion: None,
+            amplitude: None,
+            zones: None,
+            zones_statistic: HashMap::new(),
+            face_tracker: None,
+            stabilization_reference: None,
+            motions: CvlMatDeque::new(frames_count),
+            flicker,
+            flicker_flags: CvlMatDeque::new(frames_count),
+            denoise_history: CvlMatDeque::new(temporal_median),
+            frame_index: 0,
+            state: ChainState::Reset,
+            failures: 0,
+            events: Vec::new(),
+            observers: Vec::new(),
+            next_observer_id: 0,
+            profiler: None,
+            taps: HashMap::new(),
+            pending_settings: None,
         }
     }
 
     pub fn set_frames(&mut self, mat_frames: &Vec<Rc<CvlMat>>) {
-        let test = mat_frames.to_owned();
-        let _ = &self.frames.extend(test);
+        self.frames.extend(mat_frames.iter().cloned());
+    }
+
+    pub fn set_zones(&mut self, layout: ZoneLayout) {
+        self.zones = Some(layout);
+        self.zones_statistic.clear();
+    }
+
+    pub fn set_bounds(&mut self, bounds: ColorBounds) -> SettingsResult<()> {
+        bounds.validate()?;
+        self.bounds = bounds;
+        Ok(())
+    }
+
+    pub fn set_face_tracker(&mut self, tracker: FaceTracker) {
+        self.face_tracker = Some(tracker);
+    }
+
+    /// Returns current settings of chain. The settings are changed by
+    /// [`update_settings`](ChainProcessing::update_settings) only, so they are always valid.
+    pub fn settings(&self) -> &ProcessingSettings {
+        &self.settings
+    }
+
+    /// This method validates passed settings and schedules them to be applied before next
+    /// frame. The history buffers are truncated to new frames count, and if new settings
+    /// change processing of frames the frames and statistics history is reset. The
+    /// [`SettingsChanged`](ChainEvent::SettingsChanged) event is emitted on applying.
+    ///
+    /// ## Errors:
+    /// Returns [`SettingsError`] if passed settings are invalid.
+    pub fn update_settings(&mut self, proc_settings: ProcessingSettings) -> SettingsResult<()> {
+        proc_settings.validate()?;
+        self.pending_settings = Some(proc_settings);
+        Ok(())
+    }
+
+    /// Returns index of last frame passed to chain.
+    pub fn frame_index(&self) -> usize {
+        self.frame_index
+    }
+
+    /// Returns and clears events emitted since last call.
+    pub fn take_events(&mut self) -> Vec<ChainEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// This method registers observer which is notified after each executed stage, collected
+    /// statistic and emitted event (see [`ChainObserver`]).
+    ///
+    /// ## Returns:
+    /// Returns identifier of observer to remove it by
+    /// [`remove_observer`](ChainProcessing::remove_observer).
+    pub fn add_observer(&mut self, observer: Box<dyn ChainObserver>) -> usize {
+        let observer_id = self.next_observer_id;
+        self.next_observer_id += 1;
+        self.observers.push((observer_id, observer));
+        observer_id
+    }
+
+    /// Removes and returns observer by passed identifier or `None` if there is no such observer.
+    pub fn remove_observer(&mut self, observer_id: usize) -> Option<Box<dyn ChainObserver>> {
+        let index = self
+            .observers
+            .iter()
+            .position(|(id, _)| *id == observer_id)?;
+        Some(self.observers.remove(index).1)
+    }
+
+    /// This method enables or disables recording of stages latencies, allocations and frames
+    /// throughput. The recorded data is dropped on disabling. Allocations are counted only if
+    /// [`CountingAllocator`](crate::api::profile::CountingAllocator) is installed as global
+    /// allocator.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        match enabled {
+            true => {
+                self.profiler.get_or_insert_with(Profiler::default);
+            }
+            false => self.profiler = None,
+        }
+    }
+
+    /// Returns profiling report of frames processed since profiling has been enabled or reset,
+    /// or `None` if profiling is disabled.
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.profiler.as_ref().map(Profiler::report)
+    }
+
+    /// Clears recorded profiling data.
+    pub fn reset_profiling(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.reset();
+        }
     }
 
-    pub fn settings(&mut self) -> &mut ProcessingSettings {
-        &mut self.settings
+    /// Returns state of chain after last executed stage.
+    pub fn state(&self) -> ChainState {
+        self.state
+    }
+
+    /// This method clears frames, statistics and dispersion history, so the chain starts
+    /// warming up again. It should be called when video stream is restarted. The settings,
+    /// bounds and zones layout are kept.
+    pub fn reset(&mut self) {
+        self.clear_history();
+        self.motions.clear();
+        self.flicker_flags.clear();
+        if let Some(tracker) = self.face_tracker.as_mut() {
+            tracker.reset();
+        }
+
+        self.result = Ok(CvlMat::default());
+        self.source = CvlMat::default();
+        self.state = ChainState::Reset;
+        self.failures = 0;
     }
 
     pub fn run_chain(&mut self, mat: CvlMat) -> &mut Self {
+        self.apply_pending_settings();
+        if !matches!(self.state, ChainState::Degraded { .. }) {
+            self.failures = 0;
+        }
+
+        self.frame_index += 1;
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record_frame();
+        }
+
+        self.taps.clear();
+        self.source = mat.clone();
         self.result = Ok(mat);
         self
     }
 
+    pub fn track_face(&mut self) -> &mut Self {
+        self.exec_stage("track_face", |chain, input| {
+            let face = match chain.face_tracker.as_mut() {
+                None => return Ok(input.to_owned()),
+                Some(tracker) => tracker.update(input)?,
+            };
+
+            face.map(|_| input.to_owned())
+                .ok_or(ProcessingError::NoFaceDetected)
+        })
+    }
+
+    pub fn resize(&mut self) -> &mut Self {
+        self.exec_stage("resize", |chain, input| {
+            let (rows, cols) = chain
+                .settings
+                .resize
+                .target_size(input.rows(), input.columns());
+            gen_resized_frame(input, rows, cols)
+        })
+    }
+
     pub fn grayscale(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Ok(res) => gen_grayscale_frame(res),
-            Err(err) => {
-                let msg = format!("Failed exec grayscale chain function: {}", err);
-                Err(ProcessingError::GenGrayScale(msg))
+        self.exec_stage("grayscale", |_, input| gen_grayscale_frame(input))
+    }
+
+    pub fn stabilize(&mut self) -> &mut Self {
+        self.exec_stage("stabilize", |chain, input| {
+            let region = chain.roi_rect(input.rows(), input.columns());
+            let reference = chain.stabilization_reference.as_ref();
+            let method = chain.settings.stabilization;
+            let (stabilized, motion) = stabilize_frame(reference, input, method, region)?;
+
+            chain.stabilization_reference = Some(stabilized.clone());
+            let _ = chain.motions.push(motion);
+
+            Ok(stabilized)
+        })
+    }
+
+    pub fn deflicker(&mut self) -> &mut Self {
+        if self.settings.flicker_window == 0 {
+            return self;
+        }
+
+        self.exec_stage("deflicker", |chain, input| {
+            chain.flicker.set_window(chain.settings.flicker_window);
+            chain
+                .flicker
+                .set_threshold(chain.settings.flicker_threshold);
+            let (frame, is_compensated) = chain.flicker.process(input)?;
+
+            let _ = chain.flicker_flags.push(is_compensated);
+
+            Ok(frame)
+        })
+    }
+
+    pub fn denoise(&mut self) -> &mut Self {
+        self.exec_stage("denoise", |chain, input| {
+            let frame = gen_denoised_frame(input, &chain.settings.denoise)?;
+            if chain.settings.temporal_median <= 1 {
+                return Ok(frame);
             }
-        };
 
-        self
+            let is_same_size = chain.denoise_history.last().map_or(true, |last| {
+                last.rows() == frame.rows() && last.columns() == frame.columns()
+            });
+
+            if !is_same_size {
+                chain.denoise_history.clear();
+            }
+
+            let _ = chain.denoise_history.push(Rc::new(frame));
+            gen_temporal_median_frame(&chain.denoise_history)
+        })
     }
 
     pub fn canny(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Ok(res) => gen_canny_frame_by_sigma(
-                res,
-                self.settings.canny_ksize,
-                self.settings.canny_sigma,
-                self.settings.canny_is_l2,
-            ),
-            Err(err) => {
-                let msg = format!("Failed exec canny chain function: {}", err);
-                Err(ProcessingError::GenCanny(msg))
+        self.exec_stage("canny", |chain, input| {
+            let (ksize, sigma, is_l2) = (
+                chain.settings.canny_ksize,
+                chain.settings.canny_sigma,
+                chain.settings.canny_is_l2,
+            );
+
+            match &chain.settings.illumination {
+                Illumination::None => gen_canny_frame_by_mean_legacy(input, ksize, sigma, is_l2),
+                illumination => gen_illumination_frame(input, illumination)
+                    .and_then(|frame| gen_canny_frame_by_mean_legacy(&frame, ksize, sigma, is_l2)),
             }
-        };
+        })
+    }
 
-        self
+    pub fn edges(&mut self) -> &mut Self {
+        self.exec_stage("edges", |chain, input| match &chain.settings.illumination {
+            Illumination::None => detect_edges(input, &chain.settings),
+            illumination => gen_illumination_frame(input, illumination)
+                .and_then(|frame| detect_edges(&frame, &chain.settings)),
+        })
+    }
+
+    pub fn threshold(&mut self, thresh: f64, maxval: f64) -> &mut Self {
+        self.stage(&mut ThresholdStage::new(thresh, maxval))
+    }
+
+    pub fn morphology(&mut self, operation: Morphology, ksize: i32, iterations: i32) -> &mut Self {
+        self.stage(&mut MorphologyStage::new(operation, ksize, iterations))
+    }
+
+    /// This method executes passed custom stage with result of previous stage. The stage is
+    /// borrowed for single frame only, so its state is kept by caller between frames. The stage
+    /// is reported by its [`name`](ChainStage::name).
+    pub fn stage<S: ChainStage + ?Sized>(&mut self, stage: &mut S) -> &mut Self {
+        let name = stage.name();
+        self.exec_stage(name, |_, input| stage.process(input))
+    }
+
+    /// This method executes passed closure with result of previous stage. The passed name is
+    /// used by profiling report, observer events and errors of chain.
+    pub fn map<F>(&mut self, name: &'static str, func: F) -> &mut Self
+    where
+        F: FnOnce(&CvlMat) -> ProcessingResult,
+    {
+        self.exec_stage(name, |_, input| func(input))
     }
 
     pub fn append_frame(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Err(_) => Err(ProcessingError::GenAbs),
-            Ok(res) => {
-                let frame = res.to_owned();
-                let _ = &self.frames.push(Rc::new(frame));
-                Ok(CvlMat::default())
+        self.exec_stage("append_frame", |chain, input| {
+            let frame = chain.apply_roi(input)?;
+            let _ = chain.frames.push(Rc::new(frame));
+            Ok(CvlMat::default())
+        })
+    }
+
+    pub fn reduce_abs(&mut self) -> &mut Self {
+        self.exec_stage("reduce_abs", |chain, _| {
+            chain.check_frames_count()?;
+            let _ = chain.frames.take_first();
+            gen_abs_frame_reduce(&chain.frames)
+        })
+    }
+
+    pub fn abs_recursively(&mut self) -> &mut Self {
+        self.exec_stage("abs_recursively", |chain, _| {
+            chain.check_frames_count()?;
+            let _ = chain.frames.take_first();
+            gen_abs_frame(&chain.frames)
+        })
+    }
+
+    pub fn amplitude(&mut self) -> &mut Self {
+        self.exec_stage("amplitude", |chain, _| {
+            chain.check_frames_count()?;
+            let _ = chain.frames.take_first();
+
+            let amplitude = gen_amplitude_frame(&chain.frames)?;
+            let accumulated = match &chain.amplitude {
+                None => amplitude,
+                Some(prev) => {
+                    let decay = chain.settings.amplitude_decay;
+                    gen_accumulated_amplitude_frame(prev, &amplitude, decay)?
+                }
+            };
+
+            chain.amplitude = Some(accumulated.to_owned());
+            Ok(accumulated)
+        })
+    }
+
+    pub fn neighbours(&mut self) -> &mut Self {
+        self.exec_stage("neighbours", |chain, input| {
+            let (neighbours, window_size) = (chain.settings.neighbours, chain.settings.window_size);
+            compute_neighbours_map(input, neighbours, window_size)
+        })
+    }
+
+    pub fn colorize(&mut self) -> &mut Self {
+        self.exec_stage("colorize", |chain, input| {
+            let max_value = match input.typ() {
+                CV_32S => (2 * chain.settings.window_size).pow(2) as f64,
+                _ => AMPLITUDE_MAX_VALUE,
+            };
+
+            let colormap = &chain.settings.colormap;
+            let rendered = render_map(input, colormap, max_value)?;
+            match chain.settings.colorbar {
+                false => Ok(rendered),
+                true => render_colorbar(colormap, &chain.bounds, max_value, rendered.rows())
+                    .and_then(|colorbar| attach_colorbar(&rendered, &colorbar)),
             }
-        };
+        })
+    }
 
-        self
+    pub fn vibrating(&mut self) -> &mut Self {
+        self.exec_stage("vibrating", |chain, input| {
+            let (rows, cols) = (input.rows(), input.columns());
+            let scale = ResolutionScale::new(cols, chain.settings.reference_width);
+            let neighbours = scale.scale_length(chain.settings.neighbours);
+            let window_size = scale.scale_length(chain.settings.window_size);
+            let bounds = scale.scale_bounds(&chain.bounds);
+
+            // The frames are masked while appending, so difference image is already masked and
+            // only area of region is required to normalize statistic.
+            let mut mat = compute_vibration(input, neighbours, window_size, &bounds)?;
+            let mut stat = mat
+                .statistic()
+                .cloned()
+                .ok_or(ProcessingError::ComputeStatistic)?;
+            if let Some(area) = chain.roi_area(rows, cols)? {
+                stat.area = area;
+            }
+
+            let stat = scale.normalize_statistic(&stat);
+            mat.set_statistic(stat.clone());
+            let _ = chain.statistics.push(stat);
+
+            if let Some(layout) = &chain.zones {
+                update_zones_statistic(
+                    layout,
+                    &mut chain.zones_statistic,
+                    input,
+                    &chain.settings,
+                    &scale,
+                )?;
+            }
+
+            Ok(mat)
+        })
     }
 
-    pub fn reduce_abs(&mut self) -> &mut Self {
-        let frames_count = &self.frames.len();
-        if frames_count < &self.settings.frames_count {
-            self.result = Err(ProcessingError::GenAbs);
-            return self;
-        }
+    pub fn statistic(&mut self) -> &mut Self {
+        self.exec_stage("statistic", |chain, input| {
+            // The statistic of frame has been already appended to history by `vibrating`.
+            let first_stat = input.statistic().ok_or(ProcessingError::ComputeStatistic)?;
+            let old_stats = chain.statistics.iter().collect::<Vec<&Statistic>>();
 
-        self.result = match &self.result {
-            Err(_) => Err(ProcessingError::GenAbs),
-            Ok(_) => {
-                let _ = &self.frames.remove(0);
-                gen_abs_frame_reduce(&self.frames)
+            if old_stats.len() >= chain.settings.frames_count {
+                let normalization = chain.settings.normalization;
+                let is_full_frame = chain.settings.roi.is_full() && chain.face_tracker.is_none();
+                let dispersion = match is_full_frame {
+                    true => compute_statistic(old_stats, normalization),
+                    false => compute_density_statistic(old_stats, normalization),
+                };
+                chain.dispersion = Some(dispersion);
             }
-        };
 
+            for (_, observer) in chain.observers.iter_mut() {
+                observer.on_statistic(first_stat, chain.dispersion.as_ref());
+            }
+
+            let (frames_count, normalization) =
+                (chain.settings.frames_count, chain.settings.normalization);
+            chain
+                .zones_statistic
+                .values_mut()
+                .for_each(|zone| zone.update_dispersion(frames_count, normalization));
+
+            Ok(input.to_owned())
+        })
+    }
+
+    pub fn overlay(&mut self) -> &mut Self {
+        self.exec_stage("overlay", |chain, input| {
+            render_overlay(
+                &chain.source,
+                input,
+                chain.settings.overlay_opacity,
+                chain.settings.overlay_grayscale,
+            )
+        })
+    }
+
+    /// This method returns snapshot of settings, bounds, frames and statistics history of chain,
+    /// so the chain may be restored by [`from_snapshot`](ChainProcessing::from_snapshot) without
+    /// warming up. Face tracker, zones, observers and stabilization history are not included.
+    ///
+    /// ## Errors:
+    /// Returns [`SnapshotError`] if failed while trying to copy history frames.
+    pub fn snapshot(&self) -> SnapshotResult<ChainSnapshot> {
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| MatSnapshot::new(frame))
+            .collect::<SnapshotResult<Vec<MatSnapshot>>>()?;
+
+        Ok(ChainSnapshot {
+            version: SNAPSHOT_VERSION,
+            frame_index: self.frame_index,
+            settings: self.settings.clone(),
+            roi: RoiSnapshot::new(&self.settings.roi)?,
+            bounds: Some([1, 2, 3, 4].map(|index| self.bounds.get(index))),
+            frames,
+            statistics: self.statistics.iter().cloned().collect(),
+            dispersion: self.dispersion.clone(),
+            amplitude: self.amplitude.as_ref().map(MatSnapshot::new).transpose()?,
+        })
+    }
+
+    /// This method returns new chain restored from passed snapshot. The history is truncated
+    /// to frames count of snapshot settings.
+    ///
+    /// ## Errors:
+    /// Returns [`Settings`](SnapshotError::Settings) if snapshot settings or bounds are invalid
+    /// and [`Frame`](SnapshotError::Frame) if failed while trying to restore history frames.
+    pub fn from_snapshot(snapshot: ChainSnapshot) -> SnapshotResult<Self> {
+        let mut proc_settings = snapshot.settings.clone();
+        proc_settings.roi = snapshot.roi.to_roi()?;
+
+        let mut chain = ChainProcessing::new(proc_settings)?;
+        if let Some(bounds) = snapshot.color_bounds() {
+            chain.set_bounds(bounds)?;
+        }
+
+        for frame in snapshot.frames.iter() {
+            let _ = chain.frames.push(Rc::new(frame.to_cvlmat()?));
+        }
+
+        chain.statistics.extend(snapshot.statistics);
+        chain.dispersion = snapshot.dispersion;
+        chain.amplitude = snapshot
+            .amplitude
+            .as_ref()
+            .map(MatSnapshot::to_cvlmat)
+            .transpose()?;
+        chain.frame_index = snapshot.frame_index;
+        Ok(chain)
+    }
+
+    /// This method writes snapshot of chain to passed file (see
+    /// [`snapshot`](ChainProcessing::snapshot)).
+    pub fn save_snapshot(&self, path: &Path) -> SnapshotResult<()> {
+        self.snapshot()?.save(path)
+    }
+
+    /// This method returns new chain restored from snapshot file (see
+    /// [`from_snapshot`](ChainProcessing::from_snapshot)).
+    pub fn load_snapshot(path: &Path) -> SnapshotResult<Self> {
+        ChainProcessing::from_snapshot(ChainSnapshot::load(path)?)
+    }
+
+    /// This method stores result of previous stage with passed name, so intermediate results
+    /// of frame are available after chain run (see [`get_taps`](ChainProcessing::get_taps)).
+    /// The stored results are cleared on next frame.
+    pub fn tap(&mut self, name: &str) -> &mut Self {
+        self.taps.insert(name.to_string(), self.result.clone());
         self
     }
 
-    pub fn abs_recursively(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Err(_) => Err(ProcessingError::GenAbs),
-            Ok(_) => {
-                let _ = &self.frames.remove(0);
-                gen_abs_frame(&self.frames)
-            }
+    /// This method executes passed branch of stages with result of previous stage and stores
+    /// branch result with passed name (like [`tap`](ChainProcessing::tap)). The result of
+    /// previous stage, bounds, frames, statistics, stabilization, flicker, denoise and face
+    /// tracking history are restored after branch, so several branches share the same upstream
+    /// stages and don't affect each other. The statistic of branch vibration image is kept by
+    /// result frame.
+    pub fn branch<F>(&mut self, name: &str, func: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self),
+    {
+        let snapshot = BranchSnapshot {
+            result: self.result.clone(),
+            bounds: self.bounds,
+            frames: self.frames.clone(),
+            statistics: self.statistics.clone(),
+            dispersion: self.dispersion.clone(),
+            amplitude: self.amplitude.clone(),
+            zones_statistic: self.zones_statistic.clone(),
+            stabilization_reference: self.stabilization_reference.clone(),
+            motions: self.motions.clone(),
+            flicker: self.flicker.clone(),
+            flicker_flags: self.flicker_flags.clone(),
+            denoise_history: self.denoise_history.clone(),
+            face_tracking: self.face_tracker.as_ref().map(FaceTracker::tracking_state),
+            state: self.state,
+            failures: self.failures,
         };
 
+        func(self);
+
+        let branch_result = std::mem::replace(&mut self.result, snapshot.result);
+        self.taps.insert(name.to_string(), branch_result);
+        self.bounds = snapshot.bounds;
+        self.frames = snapshot.frames;
+        self.statistics = snapshot.statistics;
+        self.dispersion = snapshot.dispersion;
+        self.amplitude = snapshot.amplitude;
+        self.zones_statistic = snapshot.zones_statistic;
+        self.stabilization_reference = snapshot.stabilization_reference;
+        self.motions = snapshot.motions;
+        self.flicker = snapshot.flicker;
+        self.flicker_flags = snapshot.flicker_flags;
+        self.denoise_history = snapshot.denoise_history;
+        if let (Some(tracker), Some(state)) = (self.face_tracker.as_mut(), snapshot.face_tracking) {
+            tracker.restore_tracking_state(state);
+        }
+        self.state = snapshot.state;
+        self.failures = snapshot.failures;
         self
     }
 
-    pub fn vibrating(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Err(_) => Err(ProcessingError::GenAbs),
-            Ok(result_frame) => {
-                let result = compute_vibration(
-                    result_frame,
-                    self.settings.neighbours,
-                    self.settings.window_size,
-                    &self.bounds,
-                );
-
-                match result {
-                    Err(err) => Err(err),
-                    Ok(mat) => {
-                        let stat = mat.statistic().unwrap();
-                        self.statistics.push(stat.clone());
-                        if self.statistics.len() > self.settings.frames_count {
-                            self.statistics.remove(0);
-                        }
-                        Ok(mat)
-                    }
-                }
+    /// This method replaces color bounds used by following stages. It is intended to process
+    /// the same upstream result with other bounds within [`branch`](ChainProcessing::branch).
+    pub fn with_bounds(&mut self, bounds: ColorBounds) -> &mut Self {
+        if self.result.is_ok() {
+            match bounds.validate() {
+                Ok(_) => self.bounds = bounds,
+                Err(err) => self.result = Err(ProcessingError::Settings(err)),
             }
-        };
+        }
 
         self
     }
 
-    pub fn statistic(&mut self) -> &mut Self {
-        self.result = match &self.result {
-            Err(_) => Err(ProcessingError::ComputeStatistic),
-            Ok(res_mat) => {
-                let first_stat = res_mat.statistic().unwrap();
-                self.statistics.push(first_stat.to_owned());
-                let old_stats = self.statistics.iter().collect::<Vec<&Statistic>>();
-
-                if old_stats.len() >= self.settings.frames_count {
-                    let dispersion = compute_statistic(old_stats, self.settings.normalization);
-                    self.dispersion = Some(dispersion);
-                }
+    /// Returns results stored by [`tap`](ChainProcessing::tap) and
+    /// [`branch`](ChainProcessing::branch) for last frame.
+    pub fn get_taps(&self) -> &HashMap<String, ProcessingResult> {
+        &self.taps
+    }
 
-                Ok(res_mat.to_owned())
-            }
-        };
+    /// Returns successful result stored with passed name for last frame.
+    pub fn get_tap(&self, name: &str) -> Option<&CvlMat> {
+        self.taps.get(name).and_then(|result| result.as_ref().ok())
+    }
 
-        self
+    pub fn get_amplitude(&self) -> Option<&CvlMat> {
+        self.amplitude.as_ref()
+    }
+
+    pub fn get_motions(&self) -> &CvlMatDeque<Motion> {
+        &self.motions
+    }
+
+    /// Returns flags of last frames whether flicker compensation has been applied.
+    pub fn get_flicker_flags(&self) -> &CvlMatDeque<bool> {
+        &self.flicker_flags
+    }
+
+    pub fn get_face(&self) -> Option<Rect> {
+        self.face_tracker.as_ref().and_then(FaceTracker::face)
+    }
+
+    pub fn get_zones_statistic(&self) -> &HashMap<String, ZoneStatistic> {
+        &self.zones_statistic
     }
 
     pub fn get_dispersion(&self) -> Option<&Dispersion> {
@@ -197,12 +967,285 @@ impl ChainProcessing {
     }
 
     pub fn get_result(&self) -> ChainResult {
-        match &self.result {
-            Ok(res) => Ok(res.to_owned()),
+        self.result.clone()
+    }
+
+    /// Returns result of last executed stage which distinguishes warming up from failures.
+    pub fn output(&self) -> ChainOutput {
+        ChainOutput::from(self.result.clone())
+    }
+
+    /// This method executes passed stage function with result of previous stage. If previous
+    /// stage has failed its error is kept, otherwise the error of stage function is wrapped
+    /// with stage name, frame index and input Mat description.
+    fn exec_stage<F>(&mut self, stage: &'static str, func: F) -> &mut Self
+    where
+        F: FnOnce(&mut Self, &CvlMat) -> ProcessingResult,
+    {
+        let input = match std::mem::replace(&mut self.result, Ok(CvlMat::default())) {
+            Ok(input) => input,
             Err(err) => {
-                let msg = format!("Failed exec canny: {}", err);
-                Err(ProcessingError::ComputeVibration(msg))
+                self.result = Err(err);
+                return self;
             }
+        };
+
+        let allocations = AllocationStats::current();
+        let started = Instant::now();
+        let result = func(self, &input);
+        let elapsed = started.elapsed();
+
+        if let Some(profiler) = self.profiler.as_mut() {
+            let allocations = AllocationStats::current().since(&allocations);
+            profiler.record_stage(stage, elapsed, allocations);
         }
+
+        self.result = match result {
+            Ok(output) => {
+                for (_, observer) in self.observers.iter_mut() {
+                    observer.on_stage(stage, &output, elapsed);
+                }
+                Ok(output)
+            }
+            Err(err) if !err.is_failure() => Err(err),
+            Err(err) => Err(ProcessingError::Chain {
+                stage,
+                frame_index: self.frame_index,
+                input: MatInfo::from(input.frame()),
+                source: Box::new(err),
+            }),
+        };
+
+        self.update_state();
+        self
     }
+
+    fn update_state(&mut self) {
+        self.state = match &self.result {
+            Ok(_) => ChainState::Ready,
+            Err(ProcessingError::NotEnoughFrames {
+                required,
+                available,
+            }) => ChainState::WarmingUp {
+                collected: *available,
+                required: *required,
+            },
+            Err(ProcessingError::NoFaceDetected) => ChainState::NoFace,
+            Err(_) => {
+                self.failures += 1;
+                ChainState::Degraded {
+                    failures: self.failures,
+                }
+            }
+        };
+    }
+
+    fn check_frames_count(&self) -> Result<(), ProcessingError> {
+        let (available, required) = (self.frames.length(), self.settings.frames_count);
+        match available < required {
+            false => Ok(()),
+            true => Err(ProcessingError::NotEnoughFrames {
+                required,
+                available,
+            }),
+        }
+    }
+
+    fn apply_pending_settings(&mut self) {
+        let proc_settings = match self.pending_settings.take() {
+            Some(proc_settings) => proc_settings,
+            None => return,
+        };
+
+        let is_segmented = !is_statistic_compatible(&self.settings, &proc_settings);
+        if is_segmented {
+            self.clear_history();
+        }
+
+        let frames_count = proc_settings.frames_count;
+        self.frames.set_max_size(frames_count);
+        self.statistics.set_max_size(frames_count);
+        self.motions.set_max_size(frames_count);
+        self.flicker_flags.set_max_size(frames_count);
+        self.denoise_history
+            .set_max_size(proc_settings.temporal_median);
+        self.flicker.set_window(proc_settings.flicker_window);
+        self.flicker.set_threshold(proc_settings.flicker_threshold);
+
+        self.settings = proc_settings;
+        self.emit(ChainEvent::SettingsChanged {
+            frame_index: self.frame_index + 1,
+            is_segmented,
+        });
+    }
+
+    fn emit(&mut self, event: ChainEvent) {
+        for (_, observer) in self.observers.iter_mut() {
+            observer.on_event(&event);
+        }
+        self.events.push(event);
+    }
+
+    fn clear_history(&mut self) {
+        self.frames.clear();
+        self.statistics.clear();
+        self.dispersion = None;
+        self.amplitude = None;
+        self.zones_statistic.clear();
+        self.stabilization_reference = None;
+        self.denoise_history.clear();
+        self.flicker.reset();
+    }
+
+    fn apply_roi(&self, frame: &CvlMat) -> ProcessingResult {
+        match self.roi_mask(frame.rows(), frame.columns())? {
+            None => Ok(frame.to_owned()),
+            Some(mask) => gen_masked_frame(frame, &mask),
+        }
+    }
+
+    fn roi_rect(&self, rows: i32, cols: i32) -> Option<Rect> {
+        match &self.face_tracker {
+            Some(tracker) => tracker.face(),
+            None => self.settings.roi.bounding_rect(rows, cols),
+        }
+    }
+
+    fn roi_area(&self, rows: i32, cols: i32) -> Result<Option<u32>, ProcessingError> {
+        let mask = match self.roi_mask(rows, cols)? {
+            Some(mask) => mask,
+            None => return Ok(None),
+        };
+
+        match count_non_zero(mask.frame()) {
+            Ok(area) => Ok(Some(area as u32)),
+            Err(err) => Err(ProcessingError::opencv("count_non_zero", mask.frame(), err)),
+        }
+    }
+
+    fn roi_mask(&self, rows: i32, cols: i32) -> Result<Option<CvlMat>, ProcessingError> {
+        if let Some(tracker) = &self.face_tracker {
+            let face = tracker.face().ok_or(ProcessingError::NoFaceDetected)?;
+            let roi = RegionOfInterest::Rectangles(vec![face]);
+            return roi.mask(rows, cols).map(Some);
+        }
+
+        match self.settings.roi.is_full() {
+            true => Ok(None),
+            false => self.settings.roi.mask(rows, cols).map(Some),
+        }
+    }
+}
+
+/// A chain state which is restored after executing branch.
+struct BranchSnapshot {
+    result: ProcessingResult,
+    bounds: ColorBounds,
+    frames: CvlMatDeque<Rc<CvlMat>>,
+    statistics: CvlMatDeque<Statistic>,
+    dispersion: Option<Dispersion>,
+    amplitude: Option<CvlMat>,
+    zones_statistic: HashMap<String, ZoneStatistic>,
+    stabilization_reference: Option<CvlMat>,
+    motions: CvlMatDeque<Motion>,
+    flicker: FlickerCompensator,
+    flicker_flags: CvlMatDeque<bool>,
+    denoise_history: CvlMatDeque<Rc<CvlMat>>,
+    face_tracking: Option<TrackingState>,
+    state: ChainState,
+    failures: usize,
+}
+
+/// Returns `true` if frames processed with passed settings are comparable, so frames and
+/// statistics history may be kept. The presentation settings and frames count are ignored.
+fn is_statistic_compatible(current: &ProcessingSettings, other: &ProcessingSettings) -> bool {
+    let is_roi_equal = match (&current.roi, &other.roi) {
+        (RegionOfInterest::Full, RegionOfInterest::Full) => true,
+        (RegionOfInterest::Rectangles(a), RegionOfInterest::Rectangles(b)) => a == b,
+        (RegionOfInterest::Polygons(a), RegionOfInterest::Polygons(b)) => a == b,
+        _ => false,
+    };
+
+    is_roi_equal
+        && current.neighbours == other.neighbours
+        && current.window_size == other.window_size
+        && current.is_reduced_abs == other.is_reduced_abs
+        && current.canny_ksize == other.canny_ksize
+        && current.canny_sigma == other.canny_sigma
+        && current.canny_is_l2 == other.canny_is_l2
+        && current.edge_detector == other.edge_detector
+        && current.edge_threshold == other.edge_threshold
+        && current.normalization == other.normalization
+        && current.stabilization == other.stabilization
+        && current.illumination == other.illumination
+        && current.flicker_window == other.flicker_window
+        && current.denoise == other.denoise
+        && current.temporal_median == other.temporal_median
+        && current.resize == other.resize
+        && current.reference_width == other.reference_width
+}
+
+fn stabilize_frame(
+    reference: Option<&CvlMat>,
+    frame: &CvlMat,
+    method: Stabilization,
+    region: Option<Rect>,
+) -> Result<(CvlMat, Motion), ProcessingError> {
+    let reference = match reference {
+        Some(mat) if mat.rows() == frame.rows() && mat.columns() == frame.columns() => mat,
+        _ => return Ok((frame.to_owned(), Motion::default())),
+    };
+
+    if method == Stabilization::None {
+        return Ok((frame.to_owned(), Motion::default()));
+    }
+
+    let motion = estimate_frame_motion(reference, frame, method, region)?;
+    let stabilized = gen_stabilized_frame(frame, &motion)?;
+    Ok((stabilized, motion))
+}
+
+fn detect_edges(frame: &CvlMat, settings: &ProcessingSettings) -> ProcessingResult {
+    let (ksize, sigma, is_l2) = (
+        settings.canny_ksize,
+        settings.canny_sigma,
+        settings.canny_is_l2,
+    );
+
+    let edges = match settings.edge_detector {
+        EdgeDetector::Canny { low, high } => {
+            return gen_canny_frame(frame, low, high, ksize, is_l2)
+        }
+        EdgeDetector::CannySigma => return gen_auto_canny_frame(frame, ksize, sigma, is_l2),
+        EdgeDetector::Sobel { ksize } => gen_sobel_magnitude_frame(frame, ksize)?,
+        EdgeDetector::Laplacian { ksize } => gen_laplacian_frame(frame, ksize)?,
+        EdgeDetector::Scharr => gen_scharr_frame(frame)?,
+    };
+
+    match settings.edge_threshold > 0f64 {
+        false => Ok(edges),
+        true => gen_threshold_frame(&edges, settings.edge_threshold, 255.0),
+    }
+}
+
+fn update_zones_statistic(
+    layout: &ZoneLayout,
+    zones_statistic: &mut HashMap<String, ZoneStatistic>,
+    frame: &CvlMat,
+    settings: &ProcessingSettings,
+    scale: &ResolutionScale,
+) -> Result<(), ProcessingError> {
+    let neighbours = scale.scale_length(settings.neighbours);
+    let window_size = scale.scale_length(settings.window_size);
+    for zone in layout.zones(frame.rows(), frame.columns()) {
+        let bounds = scale.scale_bounds(zone.bounds());
+        let scaled_zone = Zone::new(zone.name(), zone.region().to_owned(), bounds);
+        let stat = scaled_zone.compute_statistic(frame, neighbours, window_size)?;
+        zones_statistic
+            .entry(zone.name().to_string())
+            .or_default()
+            .push(scale.normalize_statistic(&stat), settings.frames_count);
+    }
+
+    Ok(())
 }
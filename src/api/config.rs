@@ -0,0 +1,344 @@
+use crate::api::chain::{ChainProcessing, ProcessingSettings};
+use crate::api::stages::{BlurStage, Morphology};
+use crate::core::bounds::ColorBounds;
+use crate::core::denoise::Denoise;
+use crate::core::mat::CvlMat;
+use crate::core::roi::RegionOfInterest;
+use crate::errors::{ChainResult, ConfigError, ConfigResult, SettingsError};
+use opencv::core::{Point, Rect};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A stage of pipeline description which matches the same-named method of
+/// [`ChainProcessing`].
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "stage", rename_all = "snake_case", deny_unknown_fields)]
+pub enum StageConfig {
+    Resize,
+    Grayscale,
+    Deflicker,
+    Stabilize,
+    Denoise,
+    Canny,
+    Edges,
+    Threshold {
+        thresh: f64,
+        maxval: f64,
+    },
+    Morphology {
+        operation: Morphology,
+        ksize: i32,
+        #[serde(default = "default_iterations")]
+        iterations: i32,
+    },
+    Blur {
+        filter: Denoise,
+    },
+    AppendFrame,
+    ReduceAbs,
+    AbsRecursively,
+    Amplitude,
+    Neighbours,
+    Colorize,
+    Vibrating,
+    Statistic,
+    Overlay,
+}
+
+fn default_iterations() -> i32 {
+    1
+}
+
+/// A region of interest description of pipeline.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoiConfig {
+    /// The list of `[x, y, width, height]` rectangles.
+    Rectangles(Vec<[i32; 4]>),
+    /// The list of polygons of `[x, y]` points.
+    Polygons(Vec<Vec<[i32; 2]>>),
+}
+
+impl From<&RoiConfig> for RegionOfInterest {
+    fn from(value: &RoiConfig) -> Self {
+        match value {
+            RoiConfig::Rectangles(rects) => RegionOfInterest::Rectangles(
+                rects
+                    .iter()
+                    .map(|r| Rect::new(r[0], r[1], r[2], r[3]))
+                    .collect(),
+            ),
+            RoiConfig::Polygons(polygons) => RegionOfInterest::Polygons(
+                polygons
+                    .iter()
+                    .map(|points| points.iter().map(|p| Point::new(p[0], p[1])).collect())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A declarative pipeline description which may be loaded from TOML or JSON file:
+///
+/// ```toml
+/// bounds = [8, 9, 10, 11]
+///
+/// [settings]
+/// frames_count = 5
+/// stabilization = "phase_correlation"
+///
+/// [[stages]]
+/// stage = "grayscale"
+///
+/// [[stages]]
+/// stage = "morphology"
+/// operation = "close"
+/// ksize = 3
+/// ```
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    #[serde(default)]
+    pub settings: ProcessingSettings,
+    #[serde(default)]
+    pub bounds: Option<[i32; 4]>,
+    #[serde(default)]
+    pub roi: Option<RoiConfig>,
+    pub stages: Vec<StageConfig>,
+}
+
+impl PipelineConfig {
+    /// This method parses pipeline description from TOML string.
+    ///
+    /// ## Errors:
+    /// Returns [`InvalidValue`](ConfigError::InvalidValue) with path of offending key if
+    /// failed while trying to parse or validate description.
+    pub fn from_toml_str(text: &str) -> ConfigResult<Self> {
+        let deserializer = toml::Deserializer::new(text);
+        let config: PipelineConfig = serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| invalid_value(err.path().to_string(), err.inner().message()))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// This method parses pipeline description from JSON string.
+    ///
+    /// ## Errors:
+    /// Returns [`InvalidValue`](ConfigError::InvalidValue) with path of offending key if
+    /// failed while trying to parse or validate description.
+    pub fn from_json_str(text: &str) -> ConfigResult<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(text);
+        let config: PipelineConfig = serde_path_to_error::deserialize(&mut deserializer)
+            .map_err(|err| invalid_value(err.path().to_string(), err.inner()))?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// This method loads pipeline description from `.toml` or `.json` file.
+    ///
+    /// ## Errors:
+    /// Returns [`ReadFile`](ConfigError::ReadFile) if failed while trying to read file,
+    /// [`UnsupportedFormat`](ConfigError::UnsupportedFormat) if file extension is not
+    /// supported or [`InvalidValue`](ConfigError::InvalidValue) if description is invalid.
+    pub fn from_file(path: &Path) -> ConfigResult<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                let msg = format!("{}: {}", path.display(), err);
+                return Err(ConfigError::ReadFile(msg));
+            }
+        };
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => PipelineConfig::from_toml_str(&text),
+            Some("json") => PipelineConfig::from_json_str(&text),
+            _ => Err(ConfigError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// This method returns ready-to-run pipeline by description.
+    pub fn build(self) -> ConfigResult<Pipeline> {
+        self.validate()?;
+
+        let bounds = self.color_bounds();
+        let stages = self.stages.clone();
+        let mut chain = ChainProcessing::new(self.into_settings()).map_err(settings_error)?;
+        if let Some(bounds) = bounds {
+            chain.set_bounds(bounds).map_err(settings_error)?;
+        }
+
+        Ok(Pipeline { chain, stages })
+    }
+
+    /// Returns processing settings of description with applied region of interest.
+    pub fn into_settings(self) -> ProcessingSettings {
+        let mut settings = self.settings;
+        if let Some(roi) = &self.roi {
+            settings.roi = RegionOfInterest::from(roi);
+        }
+
+        settings
+    }
+
+    fn color_bounds(&self) -> Option<ColorBounds> {
+        self.bounds
+            .map(|b| ColorBounds::new(b[0], b[1], b[2], b[3]))
+    }
+
+    fn validate(&self) -> ConfigResult<()> {
+        if self.stages.is_empty() {
+            return Err(invalid_value("stages", "at least one stage is required"));
+        }
+
+        self.settings.validate().map_err(settings_error)?;
+        if let Some(bounds) = self.color_bounds() {
+            bounds.validate().map_err(settings_error)?;
+        }
+
+        for (index, stage) in self.stages.iter().enumerate() {
+            if let StageConfig::Morphology {
+                ksize, iterations, ..
+            } = stage
+            {
+                if *ksize <= 0 {
+                    let path = format!("stages[{}].ksize", index);
+                    return Err(invalid_value(path, "value must be positive"));
+                }
+                if *iterations <= 0 {
+                    let path = format!("stages[{}].iterations", index);
+                    return Err(invalid_value(path, "value must be positive"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn settings_error(err: SettingsError) -> ConfigError {
+    let path = match err.key() {
+        "bounds" => "bounds".to_string(),
+        key => format!("settings.{}", key),
+    };
+
+    invalid_value(path, err)
+}
+
+fn invalid_value(path: impl ToString, message: impl ToString) -> ConfigError {
+    ConfigError::InvalidValue {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// A processing chain built from [`PipelineConfig`] which executes described stages in order
+/// for each passed frame.
+pub struct Pipeline {
+    chain: ChainProcessing,
+    stages: Vec<StageConfig>,
+}
+
+impl Pipeline {
+    pub fn chain(&mut self) -> &mut ChainProcessing {
+        &mut self.chain
+    }
+
+    pub fn stages(&self) -> &[StageConfig] {
+        self.stages.as_slice()
+    }
+
+    /// This method replaces stages and schedules settings of passed description to be applied
+    /// before next frame (see [`ChainProcessing::update_settings`]).
+    pub fn update(&mut self, config: PipelineConfig) -> ConfigResult<()> {
+        config.validate()?;
+
+        if let Some(bounds) = config.color_bounds() {
+            self.chain.set_bounds(bounds).map_err(settings_error)?;
+        }
+
+        self.stages = config.stages.clone();
+        self.chain
+            .update_settings(config.into_settings())
+            .map_err(settings_error)
+    }
+
+    /// This method executes all described stages for passed frame and returns result.
+    pub fn run(&mut self, frame: CvlMat) -> ChainResult {
+        let chain = self.chain.run_chain(frame);
+        for stage in self.stages.iter() {
+            match stage {
+                StageConfig::Resize => chain.resize(),
+                StageConfig::Grayscale => chain.grayscale(),
+                StageConfig::Deflicker => chain.deflicker(),
+                StageConfig::Stabilize => chain.stabilize(),
+                StageConfig::Denoise => chain.denoise(),
+                StageConfig::Canny => chain.canny(),
+                StageConfig::Edges => chain.edges(),
+                StageConfig::Threshold { thresh, maxval } => chain.threshold(*thresh, *maxval),
+                StageConfig::Morphology {
+                    operation,
+                    ksize,
+                    iterations,
+                } => chain.morphology(*operation, *ksize, *iterations),
+                StageConfig::Blur { filter } => chain.stage(&mut BlurStage::new(*filter)),
+                StageConfig::AppendFrame => chain.append_frame(),
+                StageConfig::ReduceAbs => chain.reduce_abs(),
+                StageConfig::AbsRecursively => chain.abs_recursively(),
+                StageConfig::Amplitude => chain.amplitude(),
+                StageConfig::Neighbours => chain.neighbours(),
+                StageConfig::Colorize => chain.colorize(),
+                StageConfig::Vibrating => chain.vibrating(),
+                StageConfig::Statistic => chain.statistic(),
+                StageConfig::Overlay => chain.overlay(),
+            };
+        }
+
+        chain.get_result()
+    }
+}
+
+/// A watcher of pipeline description file which polls file modification time and reloads
+/// description when file has been changed.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Creates new watcher of passed file. The current file version is considered as loaded.
+    pub fn new(path: &Path) -> Self {
+        ConfigWatcher {
+            path: path.to_path_buf(),
+            modified: file_modified_time(path),
+        }
+    }
+
+    /// This method checks whether file has been changed since last poll.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(Some(PipelineConfig))` if file has been changed, `Ok(None)` if file has
+    /// not been changed, otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`ConfigError`] if failed while trying to load changed file. The failed version
+    /// is not reloaded until file is changed again.
+    pub fn poll(&mut self) -> ConfigResult<Option<PipelineConfig>> {
+        let modified = file_modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return Ok(None);
+        }
+
+        self.modified = modified;
+        PipelineConfig::from_file(&self.path).map(Some)
+    }
+}
+
+fn file_modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
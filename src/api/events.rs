@@ -0,0 +1,52 @@
+use crate::core::mat::CvlMat;
+use crate::core::statistic::{Dispersion, Statistic};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// An event emitted by [`ChainProcessing`](crate::api::chain::ChainProcessing) while
+/// processing video stream.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChainEvent {
+    /// The pending settings have been applied before processing frame with passed index.
+    /// If `is_segmented` is `true` the new settings are not comparable with previous ones, so
+    /// frames and statistics history has been reset and new statistics segment is started.
+    SettingsChanged {
+        frame_index: usize,
+        is_segmented: bool,
+    },
+}
+
+/// An observer of [`ChainProcessing`](crate::api::chain::ChainProcessing) which receives
+/// intermediate results of each processed frame. All methods do nothing by default, so
+/// observer implements only callbacks it is interested in.
+pub trait ChainObserver {
+    /// Called after chain stage has been executed successfully.
+    ///
+    /// ## Parameters:
+    /// * stage: (&str) a name of executed stage (like `canny`).
+    /// * output: (&CvlMat) a result frame of stage.
+    /// * elapsed: (Duration) a time spent to execute stage.
+    fn on_stage(&mut self, _stage: &str, _output: &CvlMat, _elapsed: Duration) {}
+
+    /// Called after statistic of frame has been collected by `statistic()` stage. The
+    /// dispersion is `None` while there are not enough statistics history.
+    fn on_statistic(&mut self, _statistic: &Statistic, _dispersion: Option<&Dispersion>) {}
+
+    /// Called when chain emits event.
+    fn on_event(&mut self, _event: &ChainEvent) {}
+}
+
+impl<T: ChainObserver + ?Sized> ChainObserver for Rc<RefCell<T>> {
+    fn on_stage(&mut self, stage: &str, output: &CvlMat, elapsed: Duration) {
+        self.borrow_mut().on_stage(stage, output, elapsed)
+    }
+
+    fn on_statistic(&mut self, statistic: &Statistic, dispersion: Option<&Dispersion>) {
+        self.borrow_mut().on_statistic(statistic, dispersion)
+    }
+
+    fn on_event(&mut self, event: &ChainEvent) {
+        self.borrow_mut().on_event(event)
+    }
+}
@@ -0,0 +1,313 @@
+use crate::core::mat::CvlMat;
+use crate::errors::ProcessingError;
+use opencv::core::{min_max_loc, Mat, Point, Rect, Scalar, Size, Vector, CV_32F};
+use opencv::dnn::{blob_from_image, read_net, Net};
+use opencv::imgproc::TM_CCOEFF_NORMED;
+use opencv::imgproc::{cvt_color, match_template, COLOR_BGR2GRAY, COLOR_GRAY2BGR};
+use opencv::objdetect::CascadeClassifier;
+use opencv::prelude::*;
+use std::sync::Arc;
+
+const CASCADE_SCALE_FACTOR: f64 = 1.1;
+const CASCADE_MIN_NEIGHBOURS: i32 = 3;
+const DNN_INPUT_SIZE: i32 = 300;
+const DNN_MEAN_VALUES: (f64, f64, f64, f64) = (104.0, 177.0, 123.0, 0.0);
+const DNN_DETECTION_SIZE: usize = 7;
+const TRACKING_THRESHOLD: f64 = 0.6;
+
+/// A face detector which may be used by [`FaceTracker`] instead of bundled OpenCV models,
+/// like detector of another library.
+pub trait FaceDetector {
+    /// Returns rectangles of faces found on passed frame.
+    ///
+    /// ## Parameters:
+    /// * frame: (&Mat) a passed BGR or grayscale video stream frame.
+    /// * gray: (&Mat) a grayscale copy of passed frame.
+    fn detect(&mut self, frame: &Mat, gray: &Mat) -> opencv::Result<Vec<Rect>>;
+}
+
+/// A face detector model loaded from local path or custom detector.
+pub enum FaceModel {
+    /// The Haar cascade classifier xml file (like `haarcascade_frontalface_default.xml`).
+    Cascade(String),
+    /// The SSD-like DNN face detector (like res10_300x300_ssd) which outputs detections
+    /// of `[batch, class, confidence, left, top, right, bottom]` format.
+    Dnn {
+        model: String,
+        config: String,
+        confidence: f32,
+    },
+    /// The custom face detector.
+    Detector(Box<dyn FaceDetector>),
+}
+
+enum FaceBackend {
+    Cascade(CascadeClassifier),
+    Dnn(Net, f32),
+    Detector(Box<dyn FaceDetector>),
+}
+
+/// A tracking state of [`FaceTracker`] which is restored after executing chain branch.
+#[derive(Clone)]
+pub(crate) struct TrackingState {
+    frame_index: usize,
+    face: Option<Rect>,
+    template: Option<Mat>,
+    frame_size: Size,
+}
+
+/// A face tracker which detects face every `detect_every` frames and tracks it by template
+/// matching in between. The expanded rectangle of face is used as region of interest of
+/// [`ChainProcessing`](crate::api::chain::ChainProcessing).
+pub struct FaceTracker {
+    backend: FaceBackend,
+    detect_every: usize,
+    expand_ratio: f64,
+    frame_index: usize,
+    face: Option<Rect>,
+    template: Option<Mat>,
+    frame_size: Size,
+}
+
+impl FaceTracker {
+    /// This method loads passed face detector model and returns new tracker.
+    ///
+    /// ## Parameters:
+    /// * model: (FaceModel) a face detector model to load.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(FaceTracker)` on success, otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`FaceModel`](ProcessingError::FaceModel) if failed while trying to load
+    /// passed model.
+    pub fn new(model: FaceModel) -> Result<Self, ProcessingError> {
+        let backend = match model {
+            FaceModel::Cascade(path) => load_cascade(&path)?,
+            FaceModel::Dnn {
+                model,
+                config,
+                confidence,
+            } => match read_net(&model, &config, "") {
+                Ok(net) => FaceBackend::Dnn(net, confidence),
+                Err(err) => {
+                    return Err(ProcessingError::FaceModel {
+                        path: model,
+                        source: Some(Arc::new(err)),
+                    })
+                }
+            },
+            FaceModel::Detector(detector) => FaceBackend::Detector(detector),
+        };
+
+        Ok(FaceTracker {
+            backend,
+            detect_every: 10,
+            expand_ratio: 1.5,
+            frame_index: 0,
+            face: None,
+            template: None,
+            frame_size: Size::default(),
+        })
+    }
+
+    pub fn set_detect_every(&mut self, frames: usize) {
+        self.detect_every = frames.max(1);
+    }
+
+    pub fn set_expand_ratio(&mut self, ratio: f64) {
+        self.expand_ratio = ratio.max(1f64);
+    }
+
+    /// Returns expanded rectangle of last found face or `None` if face has not been found.
+    pub fn face(&self) -> Option<Rect> {
+        self.face.map(|rect| self.expand_rect(rect))
+    }
+
+    pub fn reset(&mut self) {
+        self.frame_index = 0;
+        self.face = None;
+        self.template = None;
+    }
+
+    pub(crate) fn tracking_state(&self) -> TrackingState {
+        TrackingState {
+            frame_index: self.frame_index,
+            face: self.face,
+            template: self.template.clone(),
+            frame_size: self.frame_size,
+        }
+    }
+
+    pub(crate) fn restore_tracking_state(&mut self, state: TrackingState) {
+        self.frame_index = state.frame_index;
+        self.face = state.face;
+        self.template = state.template;
+        self.frame_size = state.frame_size;
+    }
+
+    /// This method detects or tracks face on passed frame and returns expanded face rectangle.
+    ///
+    /// ## Parameters:
+    /// * frame: (&CvlMat) a passed BGR or grayscale video stream frame.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(Some(Rect))` if face has been found, `Ok(None)` if there is no face on frame,
+    /// otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to detect or track
+    /// face.
+    pub fn update(&mut self, frame: &CvlMat) -> Result<Option<Rect>, ProcessingError> {
+        let is_detect_frame = self.frame_index % self.detect_every == 0;
+        self.frame_index += 1;
+        self.frame_size = Size::new(frame.columns(), frame.rows());
+
+        let gray = match convert_to_gray(frame.frame()) {
+            Ok(gray) => gray,
+            Err(err) => return Err(ProcessingError::opencv("face_gray", frame.frame(), err)),
+        };
+
+        let tracked = match (is_detect_frame, self.face, &self.template) {
+            (false, Some(face), Some(template)) => track_face(&gray, template, face),
+            _ => Ok(None),
+        };
+
+        let face = match tracked {
+            Ok(Some(rect)) => Some(rect),
+            Ok(None) => self.detect_face(frame.frame(), &gray)?,
+            Err(err) => return Err(ProcessingError::opencv("track_face", &gray, err)),
+        };
+
+        self.face = face;
+        self.template = face.and_then(|rect| Mat::roi(&gray, rect).ok());
+        Ok(self.face())
+    }
+
+    fn detect_face(&mut self, frame: &Mat, gray: &Mat) -> Result<Option<Rect>, ProcessingError> {
+        let detected = match &mut self.backend {
+            FaceBackend::Cascade(classifier) => detect_by_cascade(classifier, gray),
+            FaceBackend::Dnn(net, confidence) => detect_by_dnn(net, frame, *confidence),
+            FaceBackend::Detector(detector) => detector.detect(frame, gray),
+        };
+
+        match detected {
+            Ok(faces) => Ok(faces.into_iter().max_by_key(|rect| rect.area())),
+            Err(err) => Err(ProcessingError::opencv("detect_face", frame, err)),
+        }
+    }
+
+    fn expand_rect(&self, rect: Rect) -> Rect {
+        let width = (rect.width as f64 * self.expand_ratio) as i32;
+        let height = (rect.height as f64 * self.expand_ratio) as i32;
+        let x = (rect.x - (width - rect.width) / 2).max(0);
+        let y = (rect.y - (height - rect.height) / 2).max(0);
+        let width = width.min(self.frame_size.width - x);
+        let height = height.min(self.frame_size.height - y);
+        Rect::new(x, y, width, height)
+    }
+}
+
+fn load_cascade(path: &str) -> Result<FaceBackend, ProcessingError> {
+    let classifier = CascadeClassifier::new(path);
+    match classifier {
+        Ok(cascade) if !cascade.empty().unwrap_or(true) => Ok(FaceBackend::Cascade(cascade)),
+        Ok(_) => Err(ProcessingError::FaceModel {
+            path: path.to_string(),
+            source: None,
+        }),
+        Err(err) => Err(ProcessingError::FaceModel {
+            path: path.to_string(),
+            source: Some(Arc::new(err)),
+        }),
+    }
+}
+
+fn convert_to_gray(frame: &Mat) -> opencv::Result<Mat> {
+    let mut gray = Mat::default();
+    match frame.channels() {
+        1 => frame.copy_to(&mut gray)?,
+        _ => cvt_color(frame, &mut gray, COLOR_BGR2GRAY, 0)?,
+    };
+
+    Ok(gray)
+}
+
+fn track_face(gray: &Mat, template: &Mat, face: Rect) -> opencv::Result<Option<Rect>> {
+    let mut matching = Mat::default();
+    match_template(
+        gray,
+        template,
+        &mut matching,
+        TM_CCOEFF_NORMED,
+        &Mat::default(),
+    )?;
+
+    let mut max_value = 0f64;
+    let mut max_location = Point::default();
+    min_max_loc(
+        &matching,
+        None,
+        Some(&mut max_value),
+        None,
+        Some(&mut max_location),
+        &Mat::default(),
+    )?;
+
+    match max_value >= TRACKING_THRESHOLD {
+        false => Ok(None),
+        true => {
+            let rect = Rect::new(max_location.x, max_location.y, face.width, face.height);
+            Ok(Some(rect))
+        }
+    }
+}
+
+fn detect_by_cascade(classifier: &mut CascadeClassifier, gray: &Mat) -> opencv::Result<Vec<Rect>> {
+    let mut faces = Vector::<Rect>::new();
+    classifier.detect_multi_scale(
+        gray,
+        &mut faces,
+        CASCADE_SCALE_FACTOR,
+        CASCADE_MIN_NEIGHBOURS,
+        0,
+        Size::default(),
+        Size::default(),
+    )?;
+
+    Ok(faces.to_vec())
+}
+
+fn detect_by_dnn(net: &mut Net, frame: &Mat, confidence: f32) -> opencv::Result<Vec<Rect>> {
+    let mut bgr = Mat::default();
+    match frame.channels() {
+        1 => cvt_color(frame, &mut bgr, COLOR_GRAY2BGR, 0)?,
+        _ => frame.copy_to(&mut bgr)?,
+    };
+
+    let input_size = Size::new(DNN_INPUT_SIZE, DNN_INPUT_SIZE);
+    let mean = Scalar::from(DNN_MEAN_VALUES);
+    let blob = blob_from_image(&bgr, 1.0, input_size, mean, false, false, CV_32F)?;
+    net.set_input(&blob, "", 1.0, Scalar::default())?;
+
+    let detections = net.forward_single("")?;
+    let (width, height) = (bgr.cols() as f32, bgr.rows() as f32);
+    let faces = detections
+        .data_typed::<f32>()?
+        .chunks_exact(DNN_DETECTION_SIZE)
+        .filter(|detection| detection[2] >= confidence)
+        .map(|detection| {
+            let (left, top) = (detection[3] * width, detection[4] * height);
+            let (right, bottom) = (detection[5] * width, detection[6] * height);
+            Rect::new(
+                left as i32,
+                top as i32,
+                (right - left) as i32,
+                (bottom - top) as i32,
+            )
+        })
+        .filter(|rect| rect.width > 0 && rect.height > 0)
+        .collect();
+
+    Ok(faces)
+}
@@ -1,2 +1,9 @@
 pub mod capture;
 pub mod chain;
+pub mod config;
+pub mod events;
+pub mod face;
+pub mod profile;
+pub mod snapshot;
+pub mod stages;
+pub mod state;
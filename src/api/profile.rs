@@ -0,0 +1,321 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+const BUCKETS_COUNT: usize = 26;
+
+thread_local! {
+    static ALLOCATIONS_COUNT: Cell<u64> = const { Cell::new(0) };
+    static ALLOCATED_BYTES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A wrapper of [`System`] allocator which counts allocations to be reported by [`Profiler`].
+/// The allocations are counted only if allocator is installed as global allocator of
+/// application, otherwise allocation counts of report are zero:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+///
+/// The allocations are counted per thread, so stage reports contain allocations of the thread
+/// which runs chain only and allocations of other application threads are not charged to
+/// stages. Note that memory allocated by OpenCV library itself is not counted.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[inline(always)]
+fn count_allocation(size: usize) {
+    let _ = ALLOCATIONS_COUNT.try_with(|count| count.set(count.get() + 1));
+    let _ = ALLOCATED_BYTES.try_with(|bytes| bytes.set(bytes.get() + size as u64));
+}
+
+/// An amount of allocations counted by [`CountingAllocator`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AllocationStats {
+    pub count: u64,
+    pub bytes: u64,
+}
+
+impl AllocationStats {
+    /// Returns amount of allocations made by current thread since it has been started.
+    pub fn current() -> Self {
+        AllocationStats {
+            count: ALLOCATIONS_COUNT.try_with(Cell::get).unwrap_or_default(),
+            bytes: ALLOCATED_BYTES.try_with(Cell::get).unwrap_or_default(),
+        }
+    }
+
+    /// Returns amount of allocations since passed earlier stats.
+    pub fn since(&self, earlier: &AllocationStats) -> Self {
+        AllocationStats {
+            count: self.count.saturating_sub(earlier.count),
+            bytes: self.bytes.saturating_sub(earlier.bytes),
+        }
+    }
+}
+
+/// A histogram of latencies with exponential buckets: the upper bound of bucket with index `i`
+/// is `2^i` microseconds, the last bucket keeps all greater latencies.
+#[derive(Clone, Debug)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKETS_COUNT],
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: [0; BUCKETS_COUNT],
+            count: 0,
+            total: Duration::ZERO,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let micros = latency.as_micros().max(1);
+        let index = (u128::BITS - (micros - 1).leading_zeros()) as usize;
+        self.buckets[index.min(BUCKETS_COUNT - 1)] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn min(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            _ => self.min,
+        }
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => self.total.div_f64(count as f64),
+        }
+    }
+
+    pub fn buckets(&self) -> &[u64] {
+        self.buckets.as_slice()
+    }
+
+    /// Returns upper bound of bucket which contains passed percentile of latencies. The
+    /// result is limited by minimal and maximal recorded latencies.
+    ///
+    /// ## Parameters:
+    /// * percentile: (f64) a percentile value within `[0, 100]` range.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((percentile.clamp(0f64, 100f64) / 100f64) * self.count as f64).ceil();
+        let rank = (rank as u64).max(1);
+
+        let mut cumulative = 0u64;
+        for (index, count) in self.buckets.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= rank {
+                let bound = Duration::from_micros(1u64 << index);
+                return bound.clamp(self.min(), self.max);
+            }
+        }
+
+        self.max
+    }
+}
+
+/// A per-stage statistic of [`ProfileReport`].
+#[derive(Clone, Debug)]
+pub struct StageReport {
+    pub stage: &'static str,
+    pub latency: LatencyHistogram,
+    pub allocations: AllocationStats,
+}
+
+/// A report of chain profiling which contains latency histogram and allocations of each stage
+/// in order of execution and throughput of chain. The report may be printed as table.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileReport {
+    pub frames: u64,
+    pub elapsed: Duration,
+    pub stages: Vec<StageReport>,
+}
+
+impl ProfileReport {
+    /// Returns amount of frames processed per second of wall time.
+    pub fn fps(&self) -> f64 {
+        match self.elapsed.is_zero() {
+            true => 0f64,
+            false => self.frames as f64 / self.elapsed.as_secs_f64(),
+        }
+    }
+
+    /// Returns amount of frames processed per second of time spent by chain stages only. The
+    /// time is the sum of total latencies of all [`stages`](ProfileReport::stages), so each
+    /// executed stage is counted every time it runs: a stage called twice per frame or called
+    /// within several [`branch`](crate::api::chain::ChainProcessing::branch) adds all its
+    /// latencies. The stages skipped after failure of previous stage, `tap` and restoring of
+    /// chain history after branch are not included.
+    pub fn processing_fps(&self) -> f64 {
+        let total = self
+            .stages
+            .iter()
+            .map(|st| st.latency.total())
+            .sum::<Duration>();
+        match total.is_zero() {
+            true => 0f64,
+            false => self.frames as f64 / total.as_secs_f64(),
+        }
+    }
+
+    pub fn stage(&self, stage: &str) -> Option<&StageReport> {
+        self.stages.iter().find(|report| report.stage == stage)
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let millis = |value: Duration| value.as_secs_f64() * 1000f64;
+        writeln!(
+            f,
+            "{:<16} {:>8} {:>10} {:>10} {:>10} {:>10} {:>12} {:>10} {:>12}",
+            "stage",
+            "calls",
+            "mean,ms",
+            "p50,ms",
+            "p95,ms",
+            "max,ms",
+            "total,ms",
+            "allocs",
+            "bytes"
+        )?;
+
+        for report in self.stages.iter() {
+            let latency = &report.latency;
+            writeln!(
+                f,
+                "{:<16} {:>8} {:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>12.3} {:>10} {:>12}",
+                report.stage,
+                latency.count(),
+                millis(latency.mean()),
+                millis(latency.percentile(50f64)),
+                millis(latency.percentile(95f64)),
+                millis(latency.max()),
+                millis(latency.total()),
+                report.allocations.count,
+                report.allocations.bytes,
+            )?;
+        }
+
+        write!(
+            f,
+            "frames: {}, elapsed: {:.3} s, fps: {:.2}, processing fps: {:.2}",
+            self.frames,
+            self.elapsed.as_secs_f64(),
+            self.fps(),
+            self.processing_fps(),
+        )
+    }
+}
+
+/// A collector of chain stages latencies, allocations and throughput.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    frames: u64,
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    stages: Vec<StageReport>,
+}
+
+impl Profiler {
+    pub fn record_frame(&mut self) {
+        let now = Instant::now();
+        self.frames += 1;
+        self.started.get_or_insert(now);
+        self.finished = Some(now);
+    }
+
+    pub fn record_stage(
+        &mut self,
+        stage: &'static str,
+        latency: Duration,
+        allocations: AllocationStats,
+    ) {
+        self.finished = Some(Instant::now());
+        let index = match self.stages.iter().position(|report| report.stage == stage) {
+            Some(index) => index,
+            None => {
+                self.stages.push(StageReport {
+                    stage,
+                    latency: LatencyHistogram::default(),
+                    allocations: AllocationStats::default(),
+                });
+                self.stages.len() - 1
+            }
+        };
+
+        let report = &mut self.stages[index];
+        report.latency.record(latency);
+        report.allocations.count += allocations.count;
+        report.allocations.bytes += allocations.bytes;
+    }
+
+    pub fn report(&self) -> ProfileReport {
+        let elapsed = match (self.started, self.finished) {
+            (Some(started), Some(finished)) => finished.duration_since(started),
+            _ => Duration::ZERO,
+        };
+
+        ProfileReport {
+            frames: self.frames,
+            elapsed,
+            stages: self.stages.clone(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Profiler::default();
+    }
+}
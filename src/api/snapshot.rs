@@ -0,0 +1,257 @@
+use crate::api::chain::ProcessingSettings;
+use crate::core::bounds::ColorBounds;
+use crate::core::mat::CvlMat;
+use crate::core::roi::RegionOfInterest;
+use crate::core::statistic::{Dispersion, Statistic};
+use crate::errors::{SnapshotError, SnapshotResult};
+use opencv::core::{Mat, MatTraitConst, MatTraitConstManual, MatTraitManual, Point, Rect, Scalar};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// The current version of snapshot format. There are no previous versions to migrate, so
+/// snapshots of any other version (including 0) are rejected while loading.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A serializable state of [`ChainProcessing`](crate::api::chain::ChainProcessing) which is
+/// used to restore frames and statistics history after restart of application, so dispersion
+/// is available without warming up again.
+#[derive(Deserialize, Serialize)]
+pub struct ChainSnapshot {
+    pub version: u32,
+    #[serde(default)]
+    pub frame_index: usize,
+    #[serde(default)]
+    pub settings: ProcessingSettings,
+    #[serde(default)]
+    pub roi: RoiSnapshot,
+    #[serde(default)]
+    pub bounds: Option<[i32; 4]>,
+    #[serde(default)]
+    pub frames: Vec<MatSnapshot>,
+    #[serde(default)]
+    pub statistics: Vec<Statistic>,
+    #[serde(default)]
+    pub dispersion: Option<Dispersion>,
+    #[serde(default)]
+    pub amplitude: Option<MatSnapshot>,
+}
+
+impl ChainSnapshot {
+    /// This method writes snapshot to passed file as JSON.
+    ///
+    /// ## Errors:
+    /// Returns [`Format`](SnapshotError::Format) if failed while trying to serialize snapshot
+    /// and [`Io`](SnapshotError::Io) if failed while trying to write file.
+    pub fn save(&self, path: &Path) -> SnapshotResult<()> {
+        let text = serde_json::to_string(self).map_err(|err| {
+            let msg = format!("Failed serialize chain snapshot: {}", err);
+            SnapshotError::Format(msg)
+        })?;
+
+        fs::write(path, text).map_err(|err| {
+            let msg = format!("{}: {}", path.display(), err);
+            SnapshotError::Io(msg)
+        })
+    }
+
+    /// This method reads snapshot from passed JSON file.
+    ///
+    /// ## Errors:
+    /// Returns [`Io`](SnapshotError::Io) if failed while trying to read file,
+    /// [`Format`](SnapshotError::Format) if file is not a snapshot and
+    /// [`UnsupportedVersion`](SnapshotError::UnsupportedVersion) if snapshot version differs
+    /// from [`SNAPSHOT_VERSION`].
+    pub fn load(path: &Path) -> SnapshotResult<Self> {
+        let text = fs::read_to_string(path).map_err(|err| {
+            let msg = format!("{}: {}", path.display(), err);
+            SnapshotError::Io(msg)
+        })?;
+
+        ChainSnapshot::from_json_str(&text)
+    }
+
+    /// This method parses snapshot from JSON string.
+    ///
+    /// ## Errors:
+    /// Returns [`Format`](SnapshotError::Format) if passed string is not a snapshot and
+    /// [`UnsupportedVersion`](SnapshotError::UnsupportedVersion) if snapshot version differs
+    /// from [`SNAPSHOT_VERSION`].
+    pub fn from_json_str(text: &str) -> SnapshotResult<Self> {
+        let version = serde_json::from_str::<SnapshotVersion>(text)
+            .map_err(|err| SnapshotError::Format(err.to_string()))?
+            .version;
+
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                found: version,
+                supported: SNAPSHOT_VERSION,
+            });
+        }
+
+        serde_json::from_str::<ChainSnapshot>(text)
+            .map_err(|err| SnapshotError::Format(err.to_string()))
+    }
+
+    pub fn color_bounds(&self) -> Option<ColorBounds> {
+        self.bounds
+            .map(|b| ColorBounds::new(b[0], b[1], b[2], b[3]))
+    }
+}
+
+/// A version header which is parsed before snapshot itself.
+#[derive(Deserialize)]
+struct SnapshotVersion {
+    version: u32,
+}
+
+/// A serializable Mat object: shape, type and continuous data bytes.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MatSnapshot {
+    pub rows: i32,
+    pub cols: i32,
+    pub typ: i32,
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
+    pub data: Vec<u8>,
+}
+
+impl MatSnapshot {
+    /// This method copies data of passed frame.
+    ///
+    /// ## Errors:
+    /// Returns [`Frame`](SnapshotError::Frame) if failed while trying to read frame data.
+    pub fn new(frame: &CvlMat) -> SnapshotResult<Self> {
+        let mat_frame = frame.frame();
+        let continuous = match mat_frame.is_continuous() {
+            true => None,
+            false => Some(mat_frame.try_clone().map_err(snapshot_frame_error)?),
+        };
+
+        let data = continuous
+            .as_ref()
+            .unwrap_or(mat_frame)
+            .data_bytes()
+            .map_err(snapshot_frame_error)?
+            .to_vec();
+
+        Ok(MatSnapshot {
+            rows: mat_frame.rows(),
+            cols: mat_frame.cols(),
+            typ: mat_frame.typ(),
+            data,
+        })
+    }
+
+    /// This method creates new frame with data of snapshot.
+    ///
+    /// ## Errors:
+    /// Returns [`Frame`](SnapshotError::Frame) if failed while trying to create frame or data
+    /// size does not match frame shape and type.
+    pub fn to_cvlmat(&self) -> SnapshotResult<CvlMat> {
+        if self.rows == 0 || self.cols == 0 {
+            return Ok(CvlMat::default());
+        }
+
+        let mut mat =
+            Mat::new_rows_cols_with_default(self.rows, self.cols, self.typ, Scalar::all(0f64))
+                .map_err(snapshot_frame_error)?;
+
+        let bytes = mat.data_bytes_mut().map_err(snapshot_frame_error)?;
+        if bytes.len() != self.data.len() {
+            let msg = format!(
+                "Frame data size {} does not match {}x{} frame size {}.",
+                self.data.len(),
+                self.cols,
+                self.rows,
+                bytes.len()
+            );
+            return Err(SnapshotError::Frame(msg));
+        }
+
+        bytes.copy_from_slice(&self.data);
+        Ok(CvlMat::from(mat))
+    }
+}
+
+fn snapshot_frame_error(err: opencv::Error) -> SnapshotError {
+    SnapshotError::Frame(err.to_string())
+}
+
+/// A serializable region of interest.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoiSnapshot {
+    #[default]
+    Full,
+    Rectangles(Vec<[i32; 4]>),
+    Polygons(Vec<Vec<[i32; 2]>>),
+    Mask(MatSnapshot),
+}
+
+impl RoiSnapshot {
+    pub fn new(roi: &RegionOfInterest) -> SnapshotResult<Self> {
+        let snapshot = match roi {
+            RegionOfInterest::Full => RoiSnapshot::Full,
+            RegionOfInterest::Rectangles(rects) => RoiSnapshot::Rectangles(
+                rects
+                    .iter()
+                    .map(|r| [r.x, r.y, r.width, r.height])
+                    .collect(),
+            ),
+            RegionOfInterest::Polygons(polygons) => RoiSnapshot::Polygons(
+                polygons
+                    .iter()
+                    .map(|points| points.iter().map(|p| [p.x, p.y]).collect())
+                    .collect(),
+            ),
+            RegionOfInterest::Mask(mask) => RoiSnapshot::Mask(MatSnapshot::new(mask)?),
+        };
+
+        Ok(snapshot)
+    }
+
+    pub fn to_roi(&self) -> SnapshotResult<RegionOfInterest> {
+        let roi = match self {
+            RoiSnapshot::Full => RegionOfInterest::Full,
+            RoiSnapshot::Rectangles(rects) => RegionOfInterest::Rectangles(
+                rects
+                    .iter()
+                    .map(|r| Rect::new(r[0], r[1], r[2], r[3]))
+                    .collect(),
+            ),
+            RoiSnapshot::Polygons(polygons) => RegionOfInterest::Polygons(
+                polygons
+                    .iter()
+                    .map(|points| points.iter().map(|p| Point::new(p[0], p[1])).collect())
+                    .collect(),
+            ),
+            RoiSnapshot::Mask(mask) => RegionOfInterest::Mask(mask.to_cvlmat()?),
+        };
+
+        Ok(roi)
+    }
+}
+
+fn serialize_hex<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data.iter() {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    serializer.serialize_str(&hex)
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    if hex.len() % 2 != 0 {
+        return Err(serde::de::Error::custom("hex string must have even length"));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            let byte = hex.get(index..index + 2).unwrap_or_default();
+            u8::from_str_radix(byte, 16).map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use crate::core::denoise::Denoise;
+use crate::core::mat::CvlMat;
+use crate::errors::ProcessingResult;
+use crate::{gen_denoised_frame, gen_morphology_frame, gen_threshold_frame};
+use opencv::imgproc::{MORPH_CLOSE, MORPH_DILATE, MORPH_ERODE, MORPH_OPEN};
+use serde::{Deserialize, Serialize};
+
+/// A custom processing stage of [`ChainProcessing`](crate::api::chain::ChainProcessing)
+/// which transforms result of previous stage. The stage is borrowed mutably for each frame,
+/// so it may keep state between frames.
+pub trait ChainStage {
+    /// This method transforms passed result frame of previous stage.
+    ///
+    /// ## Parameters:
+    /// * frame: (&CvlMat) a result frame of previous stage.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+    fn process(&mut self, frame: &CvlMat) -> ProcessingResult;
+
+    /// Returns name of stage which is used by profiling report, observer events and errors
+    /// of chain, so stages should have different names to be distinguished.
+    fn name(&self) -> &'static str {
+        "stage"
+    }
+}
+
+impl<F> ChainStage for F
+where
+    F: FnMut(&CvlMat) -> ProcessingResult,
+{
+    fn process(&mut self, frame: &CvlMat) -> ProcessingResult {
+        self(frame)
+    }
+}
+
+/// A stage to binarize frame by passed threshold (see [`gen_threshold_frame`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThresholdStage {
+    pub thresh: f64,
+    pub maxval: f64,
+}
+
+impl ThresholdStage {
+    pub fn new(thresh: f64, maxval: f64) -> Self {
+        ThresholdStage { thresh, maxval }
+    }
+}
+
+impl ChainStage for ThresholdStage {
+    fn process(&mut self, frame: &CvlMat) -> ProcessingResult {
+        gen_threshold_frame(frame, self.thresh, self.maxval)
+    }
+
+    fn name(&self) -> &'static str {
+        "threshold"
+    }
+}
+
+/// A morphological operation of [`MorphologyStage`].
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Morphology {
+    Erode,
+    Dilate,
+    Open,
+    Close,
+}
+
+impl Morphology {
+    pub fn cv_type(&self) -> i32 {
+        match self {
+            Morphology::Erode => MORPH_ERODE,
+            Morphology::Dilate => MORPH_DILATE,
+            Morphology::Open => MORPH_OPEN,
+            Morphology::Close => MORPH_CLOSE,
+        }
+    }
+}
+
+/// A stage to apply morphological operation to frame (see [`gen_morphology_frame`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MorphologyStage {
+    pub operation: Morphology,
+    pub ksize: i32,
+    pub iterations: i32,
+}
+
+impl MorphologyStage {
+    pub fn new(operation: Morphology, ksize: i32, iterations: i32) -> Self {
+        MorphologyStage {
+            operation,
+            ksize,
+            iterations,
+        }
+    }
+}
+
+impl ChainStage for MorphologyStage {
+    fn process(&mut self, frame: &CvlMat) -> ProcessingResult {
+        let operation = self.operation.cv_type();
+        gen_morphology_frame(frame, operation, self.ksize, self.iterations)
+    }
+
+    fn name(&self) -> &'static str {
+        "morphology"
+    }
+}
+
+/// A stage to blur frame by passed spatial filter (see [`gen_denoised_frame`]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlurStage {
+    pub filter: Denoise,
+}
+
+impl BlurStage {
+    pub fn new(filter: Denoise) -> Self {
+        BlurStage { filter }
+    }
+}
+
+impl ChainStage for BlurStage {
+    fn process(&mut self, frame: &CvlMat) -> ProcessingResult {
+        gen_denoised_frame(frame, &self.filter)
+    }
+
+    fn name(&self) -> &'static str {
+        match self.filter {
+            Denoise::None => "blur",
+            Denoise::Gaussian { .. } => "gaussian_blur",
+            Denoise::Bilateral { .. } => "bilateral_blur",
+            Denoise::Median { .. } => "median_blur",
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::core::mat::CvlMat;
+use crate::errors::ProcessingError;
+
+/// A state of [`ChainProcessing`](crate::api::chain::ChainProcessing) which is updated by each
+/// executed chain stage.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ChainState {
+    /// The chain has been created or reset and there are no processed frames yet.
+    #[default]
+    Reset,
+    /// The chain collects frames history: `collected` of `required` frames are available.
+    WarmingUp { collected: usize, required: usize },
+    /// The frames history is collected and the last frame has been processed successfully.
+    Ready,
+    /// The face tracking is enabled, but there is no face on the last frame. It is not a
+    /// failure, so it does not increase failures count of [`Degraded`](ChainState::Degraded).
+    NoFace,
+    /// The processing of last `failures` frames one by one has failed.
+    Degraded { failures: usize },
+}
+
+impl ChainState {
+    /// Returns part of collected frames history within [0, 1] range.
+    pub fn progress(&self) -> f32 {
+        match *self {
+            ChainState::Reset => 0f32,
+            ChainState::WarmingUp {
+                collected,
+                required,
+            } => match required {
+                0 => 1f32,
+                _ => (collected as f32 / required as f32).min(1f32),
+            },
+            ChainState::Ready | ChainState::NoFace | ChainState::Degraded { .. } => 1f32,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(self, ChainState::Ready)
+    }
+}
+
+/// A result of processing frame by chain which distinguishes frames consumed to collect
+/// frames history from real failures.
+#[derive(Clone)]
+pub enum ChainOutput {
+    /// The frame has been processed successfully.
+    Ready(CvlMat),
+    /// The frame has been added to frames history, but there are not enough frames to
+    /// compute result yet.
+    WarmingUp { collected: usize, required: usize },
+    /// The face tracking is enabled, but there is no face on the frame.
+    NoFace,
+    /// The processing of frame has failed.
+    Failed(ProcessingError),
+}
+
+impl From<Result<CvlMat, ProcessingError>> for ChainOutput {
+    fn from(value: Result<CvlMat, ProcessingError>) -> Self {
+        match value {
+            Ok(frame) => ChainOutput::Ready(frame),
+            Err(ProcessingError::NotEnoughFrames {
+                required,
+                available,
+            }) => ChainOutput::WarmingUp {
+                collected: available,
+                required,
+            },
+            Err(ProcessingError::NoFaceDetected) => ChainOutput::NoFace,
+            Err(err) => ChainOutput::Failed(err),
+        }
+    }
+}
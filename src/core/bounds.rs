@@ -1,3 +1,5 @@
+use crate::errors::{SettingsError, SettingsResult};
+
 /// A red color pixel value used for marking magnitude and vibration Mat object.
 pub const RED_COLOR: (f64, f64, f64, f64) = (0.0, 0.0, 255.0, 0.0);
 
@@ -31,6 +33,33 @@ impl ColorBounds {
         }
     }
 
+    /// Creates new bounds if passed values are valid (see [`ColorBounds::validate`]).
+    pub fn try_new(ch1: i32, ch2: i32, ch3: i32, ch4: i32) -> SettingsResult<Self> {
+        let bounds = ColorBounds::new(ch1, ch2, ch3, ch4);
+        bounds.validate()?;
+        Ok(bounds)
+    }
+
+    /// Checks that bounds values are positive and sorted ascending, otherwise pixels are
+    /// silently classified to wrong channel.
+    pub fn validate(&self) -> SettingsResult<()> {
+        let values = [
+            self.channel_1,
+            self.channel_2,
+            self.channel_3,
+            self.channel_4,
+        ];
+
+        if values.iter().any(|value| *value <= 0) {
+            return Err(SettingsError::NotPositive("bounds"));
+        }
+
+        match values.windows(2).all(|pair| pair[0] <= pair[1]) {
+            true => Ok(()),
+            false => Err(SettingsError::Unsorted("bounds")),
+        }
+    }
+
     pub fn get(&self, index: i32) -> i32 {
         match index {
             1 => self.channel_1,
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// A spatial filter to suppress sensor noise of grayscale frame before edges detection. The
+/// noise of low light frames becomes isolated edges which pass the neighbours filter.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Denoise {
+    /// The frame is not filtered.
+    #[default]
+    None,
+    /// The Gaussian blur with passed odd kernel size and standard deviation.
+    Gaussian { ksize: i32, sigma: f64 },
+    /// The edge-preserving bilateral filter with passed pixel neighbourhood diameter and
+    /// sigma values in color and coordinate spaces.
+    Bilateral {
+        diameter: i32,
+        sigma_color: f64,
+        sigma_space: f64,
+    },
+    /// The median blur with passed odd kernel size.
+    Median { ksize: i32 },
+}
@@ -1,14 +1,26 @@
-use std::collections::VecDeque;
+use std::collections::{vec_deque, VecDeque};
+use std::iter::FusedIterator;
+use std::ops::{Index, IndexMut};
 
-#[derive(Clone)]
+const DEFAULT_MAX_SIZE: usize = 5;
+
+/// A fixed-capacity ring buffer which keeps last `max_size` pushed values. The storage is
+/// allocated once while creating deque, so pushing into full deque drops the oldest value and
+/// reuses its slot without moving other values. The values are indexed from the oldest to the
+/// newest.
+#[derive(Clone, Debug)]
 pub struct CvlMatDeque<T> {
+    /// The storage of deque values. Values pushed into storage directly are not limited by
+    /// [`max_size`](CvlMatDeque::max_size), so [`push`](CvlMatDeque::push) should be used.
     pub inner: VecDeque<T>,
+    max_size: usize,
 }
 
 impl<T> CvlMatDeque<T> {
     pub fn new(size: usize) -> Self {
         CvlMatDeque {
             inner: VecDeque::with_capacity(size),
+            max_size: size,
         }
     }
 
@@ -16,18 +28,51 @@ impl<T> CvlMatDeque<T> {
         self.inner.len()
     }
 
-    pub fn set_max_size(&mut self, queue_size: usize) {
-        self.inner = VecDeque::with_capacity(queue_size)
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.inner.len() >= self.max_size
     }
 
     pub fn max_size(&self) -> usize {
-        self.inner.capacity()
+        self.max_size
+    }
+
+    /// This method changes max size of deque. The newest values are kept if deque contains
+    /// more values than passed size.
+    ///
+    /// ## Parameters:
+    /// * queue_size: (usize) a new max size of deque.
+    pub fn set_max_size(&mut self, queue_size: usize) {
+        if self.inner.len() > queue_size {
+            self.inner.drain(..self.inner.len() - queue_size);
+        }
+
+        match queue_size < self.max_size {
+            true => self.inner.shrink_to(queue_size),
+            false => self.inner.reserve_exact(queue_size - self.inner.len()),
+        }
+        self.max_size = queue_size;
     }
 
     pub fn get(&self, index: usize) -> Option<&T> {
         self.inner.get(index)
     }
 
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.inner.get_mut(index)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.inner.front()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.inner.back()
+    }
+
     pub fn take_first(&mut self) -> Option<T> {
         self.inner.pop_front()
     }
@@ -36,33 +81,177 @@ impl<T> CvlMatDeque<T> {
         self.inner.pop_back()
     }
 
-    pub fn push(&mut self, value: T) {
-        let curr_len = self.length();
-        let curr_max = self.max_size();
-        match curr_len >= curr_max {
-            false => self.inner.push_back(value),
-            true => {
-                let _ = self.inner.remove(0);
-                self.inner.push_back(value);
-            }
+    /// This method appends value to the end of deque.
+    ///
+    /// ## Returns:
+    /// The oldest value if deque is full and it has been replaced.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if self.max_size == 0 {
+            return Some(value);
+        }
+
+        let mut evicted = None;
+        while self.is_full() {
+            evicted = self.inner.pop_front();
+        }
+
+        self.inner.push_back(value);
+        evicted
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.inner.iter(),
         }
     }
+
+    /// Returns iterator over all overlapping windows of passed size from the oldest values
+    /// to the newest ones. There are no windows if deque contains less values than size or
+    /// passed size is 0.
+    pub fn windows(&self, size: usize) -> Windows<'_, T> {
+        Windows {
+            deque: &self.inner,
+            start: 0,
+            size,
+        }
+    }
+
+    /// Returns values of deque as pair of slices: the first one contains the oldest values.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.inner.as_slices()
+    }
+
+    /// This method rearranges values, so they are stored from the oldest to the newest one
+    /// and may be passed as single slice. Values are not moved if deque is already contiguous.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.inner.make_contiguous()
+    }
 }
 
 impl<T> Default for CvlMatDeque<T> {
     fn default() -> Self {
-        CvlMatDeque {
-            inner: VecDeque::<T>::with_capacity(5),
-        }
+        CvlMatDeque::new(DEFAULT_MAX_SIZE)
     }
 }
 
 impl<T> From<Vec<T>> for CvlMatDeque<T> {
     fn from(value: Vec<T>) -> Self {
-        let vec_size = value.len();
-        let mut deque: CvlMatDeque<T> = CvlMatDeque::new(vec_size);
-        value.into_iter().for_each(|item| deque.push(item));
+        CvlMatDeque {
+            max_size: value.len(),
+            inner: VecDeque::from(value),
+        }
+    }
+}
 
-        deque
+impl<T> Index<usize> for CvlMatDeque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        let length = self.length();
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index {} is out of bounds of deque with length {}",
+                index, length
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<usize> for CvlMatDeque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let length = self.length();
+        self.get_mut(index).unwrap_or_else(|| {
+            panic!(
+                "index {} is out of bounds of deque with length {}",
+                index, length
+            )
+        })
+    }
+}
+
+impl<T> Extend<T> for CvlMatDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| {
+            let _ = self.push(value);
+        });
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CvlMatDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over values of [`CvlMatDeque`] from the oldest to the newest one.
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    inner: vec_deque::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
     }
 }
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An iterator over overlapping windows of [`CvlMatDeque`] values, see
+/// [`windows`](CvlMatDeque::windows). Each window is an iterator over its values.
+#[derive(Clone)]
+pub struct Windows<'a, T> {
+    deque: &'a VecDeque<T>,
+    start: usize,
+    size: usize,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+    type Item = Iter<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.start + self.size;
+        if self.size == 0 || end > self.deque.len() {
+            return None;
+        }
+
+        let window = Iter {
+            inner: self.deque.range(self.start..end),
+        };
+        self.start += 1;
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = match self.size {
+            0 => 0,
+            size => (self.deque.len() + 1).saturating_sub(self.start + size),
+        };
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Windows<'_, T> {}
+
+impl<T> FusedIterator for Windows<'_, T> {}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// An edge detector to transform grayscale frame before differencing. The Canny detectors
+/// return binary edges image, the gradient detectors return gradient magnitude image which
+/// may be binarized by passed threshold.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeDetector {
+    /// The Canny detector with fixed hysteresis thresholds.
+    Canny { low: f64, high: f64 },
+    /// The Canny detector with thresholds derived from frame intensity median by sigma value.
+    #[default]
+    CannySigma,
+    /// The magnitude of Sobel derivatives with passed aperture size.
+    Sobel { ksize: i32 },
+    /// The absolute Laplacian with passed aperture size.
+    Laplacian { ksize: i32 },
+    /// The magnitude of Scharr derivatives.
+    Scharr,
+}
@@ -0,0 +1,160 @@
+use crate::core::deque::CvlMatDeque;
+use crate::core::mat::CvlMat;
+use crate::errors::ProcessingError;
+use opencv::core::{mean, Mat};
+use opencv::prelude::*;
+
+/// A minimal frames count to detect brightness oscillation.
+const MIN_WINDOW_SIZE: usize = 2 * MIN_PERIODS_COUNT;
+
+/// A minimal part of sign changes of brightness deviations to consider oscillation periodic.
+const MIN_CROSSING_RATE: f64 = 0.4;
+
+/// A minimal part of brightness variance explained by periodic component to distinguish
+/// oscillation from sensor noise.
+const MIN_PERIODICITY: f64 = 0.9;
+
+/// A minimal count of oscillation periods within window to estimate periodic component.
+const MIN_PERIODS_COUNT: usize = 3;
+
+/// A detector and compensator of mains flicker. Fluorescent lighting pulses with 100/120 Hz
+/// and the aliased flicker appears as periodic oscillation of per-frame mean intensity. When
+/// oscillation is detected within window of last frames, each frame is multiplied by gain
+/// which returns its mean intensity to the window average.
+#[derive(Clone, Debug)]
+pub struct FlickerCompensator {
+    means: CvlMatDeque<f64>,
+    window: usize,
+    threshold: f64,
+}
+
+impl FlickerCompensator {
+    /// Creates new compensator.
+    ///
+    /// ## Parameters:
+    /// * window: (usize) a count of last frames to detect oscillation, at least 6 frames.
+    /// * threshold: (f64) a minimal relative amplitude of oscillation (like 0.01 for 1%).
+    pub fn new(window: usize, threshold: f64) -> Self {
+        let window = window.max(MIN_WINDOW_SIZE);
+        FlickerCompensator {
+            means: CvlMatDeque::new(window),
+            window,
+            threshold,
+        }
+    }
+
+    pub fn set_window(&mut self, window: usize) {
+        self.window = window.max(MIN_WINDOW_SIZE);
+        self.means.set_max_size(self.window);
+    }
+
+    pub fn set_threshold(&mut self, threshold: f64) {
+        self.threshold = threshold;
+    }
+
+    /// Returns history of per-frame mean intensity values.
+    pub fn means(&self) -> &CvlMatDeque<f64> {
+        &self.means
+    }
+
+    pub fn reset(&mut self) {
+        self.means.clear();
+    }
+
+    /// Returns `true` if periodic oscillation of mean intensity is detected within window.
+    /// The oscillation should exceed threshold amplitude and its periodic component should
+    /// explain most of brightness variance, so random sensor noise is not considered flicker.
+    pub fn is_flickering(&self) -> bool {
+        if self.means.length() < self.window {
+            return false;
+        }
+
+        let average = self.average();
+        if average <= 0f64 {
+            return false;
+        }
+
+        let deviations = self.means.iter().map(|m| m - average).collect::<Vec<f64>>();
+        let crossings = deviations
+            .windows(2)
+            .filter(|pair| pair[0] * pair[1] < 0f64)
+            .count();
+
+        let crossing_rate = crossings as f64 / (deviations.len() - 1) as f64;
+        let variance = deviations.iter().map(|d| d * d).sum::<f64>() / deviations.len() as f64;
+        let amplitude = variance.sqrt() / average;
+
+        crossing_rate >= MIN_CROSSING_RATE
+            && amplitude >= self.threshold
+            && periodicity(&deviations, variance) >= MIN_PERIODICITY
+    }
+
+    /// This method appends mean intensity of passed frame to history and returns compensated
+    /// frame if flicker is detected.
+    ///
+    /// ## Parameters:
+    /// * frame: (&CvlMat) a passed grayscale video stream frame.
+    ///
+    /// ## Returns:
+    /// Returns `Ok((CvlMat, bool))` with frame and flag whether compensation has been applied,
+    /// otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to compute mean
+    /// intensity or to apply gain to passed frame.
+    pub fn process(&mut self, frame: &CvlMat) -> Result<(CvlMat, bool), ProcessingError> {
+        let frame_mean = match mean(frame.frame(), &Mat::default()) {
+            Ok(scalar) => scalar[0],
+            Err(err) => return Err(ProcessingError::opencv("mean", frame.frame(), err)),
+        };
+
+        let _ = self.means.push(frame_mean);
+
+        if !self.is_flickering() || frame_mean <= 0f64 {
+            return Ok((frame.to_owned(), false));
+        }
+
+        let gain = self.average() / frame_mean;
+        let mut compensated = Mat::default();
+        match frame.frame().convert_to(&mut compensated, -1, gain, 0f64) {
+            Ok(_) => Ok((CvlMat::from(compensated), true)),
+            Err(err) => Err(ProcessingError::opencv("convert_to", frame.frame(), err)),
+        }
+    }
+
+    fn average(&self) -> f64 {
+        match self.means.length() {
+            0 => 0f64,
+            len => self.means.iter().sum::<f64>() / len as f64,
+        }
+    }
+}
+
+/// Returns the largest part of variance explained by periodic component of deviations. The
+/// periodic component of each period is the mean deviation of frames with the same phase.
+/// Only periods repeated at least [`MIN_PERIODS_COUNT`] times within window are checked.
+fn periodicity(deviations: &[f64], variance: f64) -> f64 {
+    if variance <= 0f64 {
+        return 0f64;
+    }
+
+    let max_period = deviations.len() / MIN_PERIODS_COUNT;
+    (2..=max_period)
+        .map(|period| {
+            let mut sums = vec![0f64; period];
+            let mut counts = vec![0usize; period];
+            for (index, deviation) in deviations.iter().enumerate() {
+                sums[index % period] += deviation;
+                counts[index % period] += 1;
+            }
+
+            let explained = (0..deviations.len())
+                .map(|index| {
+                    let phase = index % period;
+                    (sums[phase] / counts[phase] as f64).powi(2)
+                })
+                .sum::<f64>();
+            explained / (deviations.len() as f64 * variance)
+        })
+        .fold(0f64, f64::max)
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// A method to normalize illumination of grayscale frame before edges detection. The Canny
+/// thresholds are derived from frame intensity, so auto-exposure changes make the edges count
+/// jump without normalization.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Illumination {
+    /// The frame is not normalized.
+    #[default]
+    None,
+    /// The global histogram equalization.
+    Histogram,
+    /// The contrast limited adaptive histogram equalization.
+    Clahe { clip_limit: f64, tile_size: i32 },
+    /// The linear transformation of frame intensity to passed reference mean and
+    /// standard deviation values.
+    MeanStd { mean: f64, std: f64 },
+}
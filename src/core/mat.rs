@@ -65,8 +65,7 @@ impl CvlMat {
     pub fn to_f64_vec(&self) -> opencv::Result<Vec<f64>> {
         let frame = self.frame();
         let data = frame
-            .data_bytes()
-            .unwrap()
+            .data_bytes()?
             .iter()
             .map(|f| f64::from(*f))
             .collect::<Vec<f64>>();
@@ -88,14 +87,14 @@ impl CvlMat {
         new_vec
     }
 
-    pub fn new_with_data(rows: i32, cols: i32, typ: i32, bytes: &[u8]) -> Self {
+    pub fn new_with_data(rows: i32, cols: i32, typ: i32, bytes: &[u8]) -> opencv::Result<Self> {
         let mat = unsafe {
             let sizes = Vector::from_slice(&[rows, cols]);
-            let mut m = Mat::new_nd_vec(&sizes, typ).unwrap();
+            let mut m = Mat::new_nd_vec(&sizes, typ)?;
             m.set_data(bytes.as_ptr().cast_mut());
             m
         };
-        CvlMat::from(mat)
+        Ok(CvlMat::from(mat))
     }
 }
 
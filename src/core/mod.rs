@@ -1,4 +1,12 @@
 pub mod bounds;
+pub mod denoise;
 pub mod deque;
+pub mod edge;
+pub mod flicker;
+pub mod illumination;
 pub mod mat;
+pub mod motion;
+pub mod roi;
+pub mod scale;
 pub mod statistic;
+pub mod zones;
@@ -0,0 +1,50 @@
+use opencv::core::Mat;
+use serde::{Deserialize, Serialize};
+
+/// A method to estimate global motion between followed one by one frames.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stabilization {
+    /// The frames are not stabilized.
+    #[default]
+    None,
+    /// The translation is estimated by phase correlation of frames.
+    PhaseCorrelation,
+    /// The translation and rotation are estimated by enhanced correlation coefficient
+    /// maximization (ECC).
+    Ecc,
+}
+
+/// A global motion of frame relative to reference frame. The motion maps reference frame
+/// coordinates to current frame coordinates: rotation by `angle` degrees around the origin
+/// and translation by (`dx`, `dy`) pixels.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Motion {
+    pub dx: f64,
+    pub dy: f64,
+    pub angle: f64,
+    pub response: f64,
+}
+
+impl Motion {
+    pub fn new(dx: f64, dy: f64, angle: f64, response: f64) -> Self {
+        Motion {
+            dx,
+            dy,
+            angle,
+            response,
+        }
+    }
+
+    /// Returns magnitude of translation in pixels.
+    pub fn magnitude(&self) -> f64 {
+        self.dx.hypot(self.dy)
+    }
+
+    /// Returns 2x3 affine matrix (CV_64F) of motion.
+    pub fn to_affine(&self) -> opencv::Result<Mat> {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        let affine = [[cos, -sin, self.dx], [sin, cos, self.dy]];
+        Mat::from_slice_2d(&affine)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::core::mat::CvlMat;
+use crate::errors::{ProcessingError, ProcessingResult};
+use opencv::core::{compare, find_non_zero, Mat, Point, Rect, Scalar, Vector, CMP_GT, CV_8UC1};
+use opencv::imgproc::{bounding_rect, fill_poly, rectangle, FILLED, LINE_8};
+use opencv::prelude::*;
+
+/// A white color pixel value used for marking pixels within region of interest.
+const MASK_VALUE: f64 = 255.0;
+
+/// A region of interest of video stream frame. Only pixels within region are used to compute
+/// vibration image and statistic.
+#[derive(Clone, Default)]
+pub enum RegionOfInterest {
+    /// The whole frame is used for processing.
+    #[default]
+    Full,
+    /// The union of passed rectangles.
+    Rectangles(Vec<Rect>),
+    /// The union of passed polygons.
+    Polygons(Vec<Vec<Point>>),
+    /// The nonzero pixels of passed single-channel image.
+    Mask(CvlMat),
+}
+
+impl RegionOfInterest {
+    pub fn is_full(&self) -> bool {
+        matches!(self, RegionOfInterest::Full)
+    }
+
+    /// This method returns binary mask (CV_8UC1) of region of interest, the pixels within region
+    /// have 255 value, other pixels are zero.
+    ///
+    /// ## Parameters:
+    /// * rows: (i32) a rows of frame.
+    /// * cols: (i32) a columns of frame.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`Roi`](ProcessingError::Roi) if size of mask image differs from passed frame
+    /// size or [`OpenCv`](ProcessingError::OpenCv) if failed while trying to create mask.
+    pub fn mask(&self, rows: i32, cols: i32) -> ProcessingResult {
+        if let RegionOfInterest::Mask(image) = self {
+            if image.rows() != rows || image.columns() != cols {
+                let msg = "ROI mask size differs from frame size.";
+                return Err(ProcessingError::Roi(msg.to_string()));
+            }
+        }
+
+        match self.create_mask(rows, cols) {
+            Ok(mask) => Ok(CvlMat::from(mask)),
+            Err(err) => {
+                let empty = Mat::default();
+                Err(ProcessingError::opencv("roi_mask", &empty, err))
+            }
+        }
+    }
+
+    /// Returns bounding rectangle of region for frame of passed size or `None` if the whole
+    /// frame is used or region is empty.
+    pub fn bounding_rect(&self, rows: i32, cols: i32) -> Option<Rect> {
+        if self.is_full() {
+            return None;
+        }
+
+        let mask = self.mask(rows, cols).ok()?;
+        let mut points = Vector::<Point>::new();
+        find_non_zero(mask.frame(), &mut points).ok()?;
+        match points.is_empty() {
+            true => None,
+            false => bounding_rect(&points).ok(),
+        }
+    }
+
+    fn create_mask(&self, rows: i32, cols: i32) -> opencv::Result<Mat> {
+        let mut mask = Mat::zeros(rows, cols, CV_8UC1)?.to_mat()?;
+        let white = Scalar::all(MASK_VALUE);
+        match self {
+            RegionOfInterest::Full => {
+                mask.set_to(&white, &Mat::default())?;
+            }
+            RegionOfInterest::Rectangles(rects) => {
+                for rect in rects {
+                    rectangle(&mut mask, *rect, white, FILLED, LINE_8, 0)?;
+                }
+            }
+            RegionOfInterest::Polygons(polygons) => {
+                let points = polygons
+                    .iter()
+                    .map(|polygon| Vector::<Point>::from_slice(polygon))
+                    .collect::<Vector<Vector<Point>>>();
+
+                fill_poly(&mut mask, &points, white, LINE_8, 0, Point::default())?;
+            }
+            RegionOfInterest::Mask(image) => {
+                compare(image.frame(), &Scalar::all(0f64), &mut mask, CMP_GT)?;
+            }
+        };
+
+        Ok(mask)
+    }
+}
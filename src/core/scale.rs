@@ -0,0 +1,96 @@
+use crate::core::bounds::ColorBounds;
+use crate::core::statistic::Statistic;
+use serde::{Deserialize, Serialize};
+
+/// A method to resize frames before processing.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resize {
+    /// The frame is processed with source resolution.
+    #[default]
+    None,
+    /// The frame is resized to passed width keeping aspect ratio.
+    Width(i32),
+    /// The frame is resized by passed scale factor.
+    Scale(f64),
+}
+
+impl Resize {
+    /// Returns `(rows, cols)` of resized frame by passed source frame size.
+    pub fn target_size(&self, rows: i32, cols: i32) -> (i32, i32) {
+        let factor = match *self {
+            Resize::None => return (rows, cols),
+            Resize::Width(width) if cols > 0 => width as f64 / cols as f64,
+            Resize::Width(_) => return (rows, cols),
+            Resize::Scale(factor) => factor,
+        };
+
+        let scale = |value: i32| ((value as f64 * factor).round() as i32).max(1);
+        (scale(rows), scale(cols))
+    }
+}
+
+/// A scale of processed frame resolution relative to reference resolution. The `neighbours`,
+/// `window_size` and color bounds values are defined for reference width and are scaled
+/// linearly (edges are lines, so neighbours count grows with window side), the statistic
+/// pixels counts are scaled by area to reference resolution. So settings and dispersion
+/// values may be shared across cameras of different resolution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ResolutionScale {
+    factor: f64,
+}
+
+impl Default for ResolutionScale {
+    fn default() -> Self {
+        ResolutionScale { factor: 1f64 }
+    }
+}
+
+impl ResolutionScale {
+    /// Creates scale of passed frame width relative to reference width. The zero reference
+    /// width disables scaling.
+    pub fn new(frame_width: i32, reference_width: i32) -> Self {
+        match reference_width > 0 && frame_width > 0 {
+            false => ResolutionScale::default(),
+            true => ResolutionScale {
+                factor: frame_width as f64 / reference_width as f64,
+            },
+        }
+    }
+
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    /// Returns passed linear value (like window size) scaled to frame resolution. The positive
+    /// value is never scaled down to zero.
+    pub fn scale_length(&self, value: i32) -> i32 {
+        match value > 0 {
+            false => value,
+            true => ((value as f64 * self.factor).round() as i32).max(1),
+        }
+    }
+
+    /// Returns passed color bounds scaled to frame resolution.
+    pub fn scale_bounds(&self, bounds: &ColorBounds) -> ColorBounds {
+        ColorBounds::new(
+            self.scale_length(bounds.get(1)),
+            self.scale_length(bounds.get(2)),
+            self.scale_length(bounds.get(3)),
+            self.scale_length(bounds.get(4)),
+        )
+    }
+
+    /// Returns passed statistic with pixels counts scaled to reference resolution.
+    pub fn normalize_statistic(&self, statistic: &Statistic) -> Statistic {
+        let area_factor = self.factor * self.factor;
+        let scale = |value: u16| (value as f64 / area_factor).round().min(u16::MAX as f64) as u16;
+        Statistic {
+            ch1: scale(statistic.ch1),
+            ch2: scale(statistic.ch2),
+            ch3: scale(statistic.ch3),
+            ch4: scale(statistic.ch4),
+            area: (statistic.area as f64 / area_factor).round() as u32,
+        }
+    }
+}
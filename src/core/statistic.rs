@@ -1,18 +1,37 @@
-#[derive(Default, Clone, Debug)]
+use crate::errors::ProcessingError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Clone, Debug, Deserialize, Serialize)]
 pub struct Statistic {
     pub ch1: u16,
     pub ch2: u16,
     pub ch3: u16,
     pub ch4: u16,
+    pub area: u32,
 }
 
 impl Statistic {
     pub fn new(ch1: u16, ch2: u16, ch3: u16, ch4: u16) -> Self {
-        Statistic { ch1, ch2, ch3, ch4 }
+        Statistic {
+            ch1,
+            ch2,
+            ch3,
+            ch4,
+            area: 0,
+        }
+    }
+
+    /// Returns percentage of processed area pixels for each channel or zeros if area is unknown.
+    pub fn density(&self) -> [f32; 4] {
+        let channels = [self.ch1, self.ch2, self.ch3, self.ch4];
+        match self.area {
+            0 => [0f32; 4],
+            area => channels.map(|ch| ch as f32 * 100f32 / area as f32),
+        }
     }
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Deserialize, Serialize)]
 pub struct Dispersion {
     pub ch1: f32,
     pub ch2: f32,
@@ -26,13 +45,13 @@ impl Dispersion {
     }
 }
 
-impl From<Vec<f32>> for Dispersion {
-    fn from(value: Vec<f32>) -> Self {
-        Dispersion {
-            ch1: *value.first().unwrap(),
-            ch2: *value.get(1).unwrap(),
-            ch3: *value.get(2).unwrap(),
-            ch4: *value.get(3).unwrap(),
+impl TryFrom<Vec<f32>> for Dispersion {
+    type Error = ProcessingError;
+
+    fn try_from(value: Vec<f32>) -> Result<Self, Self::Error> {
+        match value.as_slice() {
+            [ch1, ch2, ch3, ch4] => Ok(Dispersion::new(*ch1, *ch2, *ch3, *ch4)),
+            _ => Err(ProcessingError::ComputeStatistic),
         }
     }
 }
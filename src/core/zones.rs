@@ -0,0 +1,141 @@
+use crate::core::bounds::ColorBounds;
+use crate::core::deque::CvlMatDeque;
+use crate::core::mat::CvlMat;
+use crate::core::roi::RegionOfInterest;
+use crate::core::statistic::{Dispersion, Statistic};
+use crate::errors::ProcessingError;
+use crate::{compute_density_statistic, compute_vibration_masked};
+use opencv::core::Rect;
+
+/// A named zone of video stream frame with own color bounds. The statistic and dispersion of
+/// each zone are computed independently, so movement in different areas (like head and hands)
+/// may be compared within one stream.
+#[derive(Clone)]
+pub struct Zone {
+    name: String,
+    region: RegionOfInterest,
+    bounds: ColorBounds,
+}
+
+impl Zone {
+    pub fn new(name: &str, region: RegionOfInterest, bounds: ColorBounds) -> Self {
+        Zone {
+            name: name.to_string(),
+            region,
+            bounds,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn region(&self) -> &RegionOfInterest {
+        &self.region
+    }
+
+    pub fn bounds(&self) -> &ColorBounds {
+        &self.bounds
+    }
+
+    /// This method returns statistic of vibrating pixels within zone for passed difference image.
+    ///
+    /// ## Parameters:
+    /// * image: (&CvlMat) a passed diff-image (results of abs).
+    /// * neighbours: (i32) a neighbours count value to filter noise of vibration.
+    /// * window_size: (i32) a offset from central pixel to compute non-null pixel neighbours.
+    ///
+    /// ## Returns:
+    /// Returns `Ok(Statistic)` on success, otherwise returns an error.
+    ///
+    /// ## Errors:
+    /// Returns [`Roi`](ProcessingError::Roi) or [`OpenCv`](ProcessingError::OpenCv) if failed
+    /// while trying to create zone mask and [`OpenCv`](ProcessingError::OpenCv) if failed while
+    /// trying to compute vibration image of zone.
+    pub fn compute_statistic(
+        &self,
+        image: &CvlMat,
+        neighbours: i32,
+        window_size: i32,
+    ) -> Result<Statistic, ProcessingError> {
+        let mask = self.region.mask(image.rows(), image.columns())?;
+        let vibration =
+            compute_vibration_masked(image, &mask, neighbours, window_size, &self.bounds)?;
+
+        Ok(vibration.statistic().cloned().unwrap_or_default())
+    }
+}
+
+/// A layout of frame zones: either passed list of named zones or automatic grid of
+/// `rows x cols` cells named like `cell_<row>_<col>`.
+#[derive(Clone)]
+pub enum ZoneLayout {
+    Zones(Vec<Zone>),
+    Grid {
+        rows: i32,
+        cols: i32,
+        bounds: ColorBounds,
+    },
+}
+
+impl ZoneLayout {
+    /// Returns zones of layout for frame of passed size.
+    pub fn zones(&self, frame_rows: i32, frame_cols: i32) -> Vec<Zone> {
+        match self {
+            ZoneLayout::Zones(zones) => zones.to_owned(),
+            ZoneLayout::Grid { rows, cols, bounds } => {
+                let (rows, cols) = ((*rows).max(1), (*cols).max(1));
+                let (cell_rows, cell_cols) = (frame_rows / rows, frame_cols / cols);
+                (0..rows)
+                    .flat_map(|row| (0..cols).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        let (x, y) = (col * cell_cols, row * cell_rows);
+                        let width = match col == cols - 1 {
+                            true => frame_cols - x,
+                            false => cell_cols,
+                        };
+                        let height = match row == rows - 1 {
+                            true => frame_rows - y,
+                            false => cell_rows,
+                        };
+
+                        let name = format!("cell_{}_{}", row, col);
+                        let rect = Rect::new(x, y, width, height);
+                        Zone::new(&name, RegionOfInterest::Rectangles(vec![rect]), *bounds)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A statistic history and dispersion of single zone.
+#[derive(Default, Clone)]
+pub struct ZoneStatistic {
+    statistics: CvlMatDeque<Statistic>,
+    dispersion: Option<Dispersion>,
+}
+
+impl ZoneStatistic {
+    pub fn statistics(&self) -> &CvlMatDeque<Statistic> {
+        &self.statistics
+    }
+
+    pub fn dispersion(&self) -> Option<&Dispersion> {
+        self.dispersion.as_ref()
+    }
+
+    pub(crate) fn push(&mut self, statistic: Statistic, history_size: usize) {
+        if self.statistics.max_size() != history_size {
+            self.statistics.set_max_size(history_size);
+        }
+        let _ = self.statistics.push(statistic);
+    }
+
+    pub(crate) fn update_dispersion(&mut self, history_size: usize, normalization: f32) {
+        if self.statistics.length() >= history_size {
+            let history = self.statistics.iter().collect::<Vec<&Statistic>>();
+            self.dispersion = Some(compute_density_statistic(history, normalization));
+        }
+    }
+}
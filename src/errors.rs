@@ -1,37 +1,112 @@
 use crate::core::mat::CvlMat;
+use opencv::core::{type_to_string, Mat, MatTraitConst};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
 use thiserror::Error;
 
 pub type ChainResult = Result<CvlMat, ProcessingError>;
 pub type ProcessingResult = Result<CvlMat, ProcessingError>;
 
-#[derive(Debug, Error)]
+/// A description of Mat object (shape and type) attached to errors.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MatInfo {
+    pub rows: i32,
+    pub cols: i32,
+    pub channels: i32,
+    pub typ: i32,
+}
+
+impl From<&Mat> for MatInfo {
+    fn from(value: &Mat) -> Self {
+        MatInfo {
+            rows: value.rows(),
+            cols: value.cols(),
+            channels: value.channels(),
+            typ: value.typ(),
+        }
+    }
+}
+
+impl Display for MatInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let typ = type_to_string(self.typ).unwrap_or_else(|_| self.typ.to_string());
+        write!(f, "{}x{} {}", self.cols, self.rows, typ)
+    }
+}
+
+#[derive(Clone, Debug, Error)]
 pub enum ProcessingError {
-    #[error("Caught error while computing frame median values.")]
-    ComputeMedian,
-    #[error("Caught error while computing frame vibrating pixels.")]
-    ComputeVibration(String),
-    #[error("Caught error while processing abs() for passed Mats.")]
-    GenAbs,
     #[error("Caught error while processing canny() for passed Mat.")]
     GenCanny(String),
-    #[error("Caught error while processing difference for both Mats.")]
-    GenDifferences(String),
-    #[error("Caught error while processing distribution for passed Mat.")]
-    GenDistribution(String),
-    #[error("Caught error while transforming Mat to grayscale.")]
-    GenGrayScale(String),
-    #[error("Caught error while transforming Mat to threshold.")]
-    GenThreshold(String),
-    #[error("Caught error while transforming Mat to sobel.")]
-    GenSobel(String),
+    #[error("Caught error while denoising passed Mats.")]
+    GenDenoise(String),
     #[error("Caught error while computing statistics.")]
     ComputeStatistic,
+    #[error("Caught error while applying region of interest.")]
+    Roi(String),
+    #[error("Caught error while loading face detector model {path}.")]
+    FaceModel {
+        path: String,
+        #[source]
+        source: Option<Arc<opencv::Error>>,
+    },
+    #[error("There is no face on passed Mat.")]
+    NoFaceDetected,
+    #[error("Invalid chain settings.")]
+    Settings(#[from] SettingsError),
+    #[error("Caught OpenCV error in {operation} for {input} Mat.")]
+    OpenCv {
+        operation: &'static str,
+        input: MatInfo,
+        #[source]
+        source: Arc<opencv::Error>,
+    },
+    #[error("Failed exec {stage} chain stage for frame {frame_index} ({input} Mat).")]
+    Chain {
+        stage: &'static str,
+        frame_index: usize,
+        input: MatInfo,
+        #[source]
+        source: Box<ProcessingError>,
+    },
+    #[error("There are {available} of {required} frames required to process.")]
+    NotEnoughFrames { required: usize, available: usize },
+}
+
+impl ProcessingError {
+    /// Returns OpenCV error of passed operation with description of passed input Mat.
+    pub fn opencv(operation: &'static str, input: &Mat, source: opencv::Error) -> Self {
+        ProcessingError::OpenCv {
+            operation,
+            input: MatInfo::from(input),
+            source: Arc::new(source),
+        }
+    }
+
+    /// Returns `false` for expected chain states (not enough frames yet or no face on frame)
+    /// which are not failures of processing.
+    pub fn is_failure(&self) -> bool {
+        !matches!(
+            self,
+            ProcessingError::NotEnoughFrames { .. } | ProcessingError::NoFaceDetected
+        )
+    }
+
+    /// Returns the innermost error of chained errors.
+    pub fn root_cause(&self) -> &ProcessingError {
+        match self {
+            ProcessingError::Chain { source, .. } => source.root_cause(),
+            err => err,
+        }
+    }
 }
 
 pub type CaptureResult = Result<(), CaptureError>;
 
 #[derive(Debug, Error)]
 pub enum CaptureError {
+    #[error("Caught error while creating video capture.")]
+    CreateCapture(String),
     #[error("Caught error while opening video stream.")]
     OpenStream(String),
     #[error("Caught error while closing video stream.")]
@@ -47,3 +122,67 @@ pub enum ReadFrameError {
     #[error("Caught error while reading next frame of stream.")]
     NextFrameError,
 }
+
+pub type SettingsResult<T> = Result<T, SettingsError>;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum SettingsError {
+    #[error("The `{0}` value must be positive.")]
+    NotPositive(&'static str),
+    #[error("The `{key}` value must be within [{min}, {max}] range.")]
+    OutOfRange {
+        key: &'static str,
+        min: f64,
+        max: f64,
+    },
+    #[error("The `{0}` kernel size must be positive and odd.")]
+    InvalidKernelSize(&'static str),
+    #[error("The `canny_ksize` aperture size must be 3, 5 or 7.")]
+    InvalidCannyKsize,
+    #[error("The `{0}` values must be sorted ascending.")]
+    Unsorted(&'static str),
+    #[error("The `colormap` custom colormap must contain 256 colors.")]
+    InvalidColormap,
+}
+
+impl SettingsError {
+    /// Returns name of settings key which value violates constraint.
+    pub fn key(&self) -> &'static str {
+        match self {
+            SettingsError::NotPositive(key) => key,
+            SettingsError::OutOfRange { key, .. } => key,
+            SettingsError::InvalidKernelSize(key) => key,
+            SettingsError::InvalidCannyKsize => "canny_ksize",
+            SettingsError::Unsorted(key) => key,
+            SettingsError::InvalidColormap => "colormap",
+        }
+    }
+}
+
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Caught error while reading config file {0}.")]
+    ReadFile(String),
+    #[error("Not supported config file format {0}.")]
+    UnsupportedFormat(String),
+    #[error("Invalid config value of `{path}`: {message}")]
+    InvalidValue { path: String, message: String },
+}
+
+pub type SnapshotResult<T> = Result<T, SnapshotError>;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Caught error while reading or writing snapshot file {0}.")]
+    Io(String),
+    #[error("Invalid chain snapshot format: {0}")]
+    Format(String),
+    #[error("Not supported snapshot version {found}, supported version is {supported}.")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("Caught error while restoring snapshot frame: {0}")]
+    Frame(String),
+    #[error("Invalid snapshot settings: {0}")]
+    Settings(#[from] SettingsError),
+}
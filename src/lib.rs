@@ -5,24 +5,36 @@ pub mod errors;
 pub mod ui;
 
 use crate::core::bounds::*;
+use crate::core::denoise::Denoise;
+use crate::core::illumination::Illumination;
 use crate::core::mat::CvlMat;
+use crate::core::motion::{Motion, Stabilization};
 use crate::core::statistic::{Dispersion, Statistic};
 use crate::errors::{ProcessingError, ProcessingResult};
 
-use ndarray::{Array, Array1};
+use opencv::core::{absdiff, add_weighted, cart_to_polar, count_non_zero, find_non_zero};
+use opencv::core::{convert_scale_abs, magnitude};
+use opencv::core::{mean_std_dev, Mat, MatExprTraitConst};
+use opencv::core::{MatTrait, MatTraitConst, MatTraitConstManual, MatTraitManual};
+use opencv::core::{Point, Rect, Scalar, Size, TermCriteria, Vector};
+use opencv::core::{TermCriteria_COUNT, TermCriteria_EPS, BORDER_CONSTANT, BORDER_REPLICATE};
+use opencv::core::{BORDER_DEFAULT, CV_16S, CV_32F, CV_32S, CV_64FC4, CV_8U, CV_8UC3};
+use opencv::imgproc::{accumulate, apply_color_map, canny, cvt_color, sobel, threshold};
+use opencv::imgproc::{bilateral_filter, gaussian_blur, median_blur};
+use opencv::imgproc::{create_clahe, equalize_hist, laplacian, scharr, CLAHETrait};
+use opencv::imgproc::{get_structuring_element, morphology_default_border_value, morphology_ex};
+use opencv::imgproc::{phase_correlate, resize, warp_affine, INTER_AREA, INTER_LINEAR};
+use opencv::imgproc::{COLOR_BGR2GRAY, MORPH_RECT, THRESH_BINARY, WARP_INVERSE_MAP};
+use opencv::video::{find_transform_ecc, MOTION_EUCLIDEAN};
 
-use opencv::core::{absdiff, cart_to_polar, count_non_zero, find_non_zero};
-use opencv::core::{Mat, MatExprTraitConst, MatTrait, MatTraitConst, MatTraitConstManual};
-use opencv::core::{Point, Rect, Scalar, Vector};
-use opencv::core::{BORDER_DEFAULT, CV_32F, CV_64FC4, CV_8UC3};
-use opencv::imgproc::{canny, cvt_color, sobel, threshold};
-use opencv::imgproc::{COLOR_BGR2GRAY, THRESH_BINARY};
-
-use std::ops::Deref;
 use std::rc::Rc;
 
 const CHANNELS_COUNT: usize = 4;
 const POW_DIFF_VALUE: u32 = 2;
+const ECC_MAX_ITERATIONS: i32 = 50;
+const ECC_EPSILON: f64 = 1e-4;
+const ECC_GAUSS_FILTER_SIZE: i32 = 5;
+const MIN_STD_DEV_VALUE: f64 = 1e-3;
 pub const BGR_CV_IMAGE: i32 = 16;
 pub const ANY_2_DIM_IMAGE: i32 = 0;
 
@@ -39,17 +51,14 @@ pub const ANY_2_DIM_IMAGE: i32 = 0;
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenGrayScale`](ProcessingError::GenGrayScale) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// transform passed image to grayscale image.
 #[inline(always)]
 pub fn gen_grayscale_frame(frame: &CvlMat) -> ProcessingResult {
     let mut gray_frame = Mat::default();
     match cvt_color(frame.frame(), &mut gray_frame, COLOR_BGR2GRAY, 0) {
         Ok(_) => Ok(CvlMat::from(gray_frame)),
-        Err(_) => {
-            let msg = "Failed while trying to transform frame to grayscale.";
-            Err(ProcessingError::GenGrayScale(msg.to_string()))
-        }
+        Err(err) => Err(ProcessingError::opencv("cvt_color", frame.frame(), err)),
     }
 }
 
@@ -68,20 +77,268 @@ pub fn gen_grayscale_frame(frame: &CvlMat) -> ProcessingResult {
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenThreshold`](ProcessingError::GenThreshold) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// transform passed image to threshold image.
 #[inline(always)]
 pub fn gen_threshold_frame(frame: &CvlMat, thresh: f64, maxval: f64) -> ProcessingResult {
     let mut gray: Mat = Mat::default();
     match threshold(frame.frame(), &mut gray, thresh, maxval, THRESH_BINARY) {
         Ok(_) => Ok(CvlMat::from(gray)),
-        Err(_) => {
-            let msg = "Failed while trying to transform frame to threshold.";
-            Err(ProcessingError::GenThreshold(msg.to_string()))
+        Err(err) => Err(ProcessingError::opencv("threshold", frame.frame(), err)),
+    }
+}
+
+/// This method returns grayscale image with normalized illumination. The histogram
+/// equalization stretches intensity distribution of the whole frame, CLAHE equalizes
+/// histograms of frame tiles with limited contrast amplification, mean/std normalization
+/// linearly transforms frame intensity to passed reference mean and standard deviation.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale (CV_8UC1) video stream frame to transform.
+/// * illumination: (&Illumination) the normalization method and its parameters.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// normalize illumination of passed image.
+pub fn gen_illumination_frame(frame: &CvlMat, illumination: &Illumination) -> ProcessingResult {
+    let mat_frame = frame.frame();
+    let mut normalized = Mat::default();
+    let result = match *illumination {
+        Illumination::None => mat_frame.copy_to(&mut normalized),
+        Illumination::Histogram => equalize_hist(mat_frame, &mut normalized),
+        Illumination::Clahe {
+            clip_limit,
+            tile_size,
+        } => {
+            let tile_grid_size = Size::new(tile_size, tile_size);
+            create_clahe(clip_limit, tile_grid_size)
+                .and_then(|mut clahe| clahe.apply(mat_frame, &mut normalized))
+        }
+        Illumination::MeanStd { mean, std } => {
+            normalize_mean_std(mat_frame, &mut normalized, mean, std)
+        }
+    };
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(normalized)),
+        Err(err) => Err(ProcessingError::opencv(
+            "normalize_illumination",
+            mat_frame,
+            err,
+        )),
+    }
+}
+
+/// Linearly transforms passed frame to have passed mean and standard deviation values.
+fn normalize_mean_std(frame: &Mat, result: &mut Mat, mean: f64, std: f64) -> opencv::Result<()> {
+    let mut frame_mean = Mat::default();
+    let mut frame_std = Mat::default();
+    mean_std_dev(frame, &mut frame_mean, &mut frame_std, &Mat::default())?;
+
+    let frame_mean = *frame_mean.at::<f64>(0)?;
+    let frame_std = frame_std.at::<f64>(0)?.max(MIN_STD_DEV_VALUE);
+    let alpha = std / frame_std;
+    let beta = mean - alpha * frame_mean;
+    frame.convert_to(result, -1, alpha, beta)
+}
+
+/// This method returns image transformed by passed morphological operation with rectangular
+/// structuring element. The erosion removes isolated pixels and thins edges, the dilation joins
+/// broken edges, the opening (erosion then dilation) removes small noise blobs and the closing
+/// (dilation then erosion) fills small gaps.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed video stream frame to transform.
+/// * operation: (i32) the morphological operation type (like `MORPH_ERODE` or `MORPH_CLOSE`).
+/// * ksize: (i32) the size of rectangular structuring element.
+/// * iterations: (i32) the number of times operation is applied.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// transform passed image.
+pub fn gen_morphology_frame(
+    frame: &CvlMat,
+    operation: i32,
+    ksize: i32,
+    iterations: i32,
+) -> ProcessingResult {
+    let mut morph_frame = Mat::default();
+    let anchor = Point::new(-1, -1);
+    let result =
+        get_structuring_element(MORPH_RECT, Size::new(ksize, ksize), anchor).and_then(|kernel| {
+            morphology_ex(
+                frame.frame(),
+                &mut morph_frame,
+                operation,
+                &kernel,
+                anchor,
+                iterations,
+                BORDER_CONSTANT,
+                morphology_default_border_value()?,
+            )
+        });
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(morph_frame)),
+        Err(err) => Err(ProcessingError::opencv("morphology_ex", frame.frame(), err)),
+    }
+}
+
+/// This method returns image resized to passed size. The area interpolation is used for
+/// downscaling to avoid moire, the linear interpolation is used for upscaling.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed video stream frame to resize.
+/// * rows: (i32) the rows count of resized image.
+/// * cols: (i32) the columns count of resized image.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// resize passed image.
+pub fn gen_resized_frame(frame: &CvlMat, rows: i32, cols: i32) -> ProcessingResult {
+    if frame.rows() == rows && frame.columns() == cols {
+        return Ok(frame.to_owned());
+    }
+
+    let interpolation = match cols < frame.columns() {
+        true => INTER_AREA,
+        false => INTER_LINEAR,
+    };
+
+    let mut resized = Mat::default();
+    let size = Size::new(cols, rows);
+    match resize(frame.frame(), &mut resized, size, 0f64, 0f64, interpolation) {
+        Ok(_) => Ok(CvlMat::from(resized)),
+        Err(err) => Err(ProcessingError::opencv("resize", frame.frame(), err)),
+    }
+}
+
+/// This method returns grayscale image filtered by passed spatial denoise method. The Gaussian
+/// blur smooths noise and edges equally, the bilateral filter smooths noise but preserves
+/// strong edges, the median blur removes salt-and-pepper noise.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale video stream frame to filter.
+/// * denoise: (&Denoise) the denoise method and its parameters.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// filter passed image.
+pub fn gen_denoised_frame(frame: &CvlMat, denoise: &Denoise) -> ProcessingResult {
+    let mat_frame = frame.frame();
+    let mut denoised = Mat::default();
+    let result = match *denoise {
+        Denoise::None => mat_frame.copy_to(&mut denoised),
+        Denoise::Gaussian { ksize, sigma } => {
+            let size = Size::new(ksize, ksize);
+            gaussian_blur(mat_frame, &mut denoised, size, sigma, sigma, BORDER_DEFAULT)
+        }
+        Denoise::Bilateral {
+            diameter,
+            sigma_color,
+            sigma_space,
+        } => bilateral_filter(
+            mat_frame,
+            &mut denoised,
+            diameter,
+            sigma_color,
+            sigma_space,
+            BORDER_DEFAULT,
+        ),
+        Denoise::Median { ksize } => median_blur(mat_frame, &mut denoised, ksize),
+    };
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(denoised)),
+        Err(err) => Err(ProcessingError::opencv("denoise", mat_frame, err)),
+    }
+}
+
+/// This method returns temporal median image by passed list of followed one by one frames.
+/// Each pixel of result image is the median of this pixel values over all passed frames, so
+/// noise which appears on single frame only is removed while static edges are kept.
+///
+/// ## Parameters:
+/// * frame_images: (I) a list (slice, vector or [`CvlMatDeque`](crate::core::deque::CvlMatDeque))
+/// of grayscale (CV_8UC1) frames of the same size.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`GenDenoise`](ProcessingError::GenDenoise) if list is empty or passed frames have
+/// different sizes or types, and [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// read or create frame data.
+pub fn gen_temporal_median_frame<'a, I>(frame_images: I) -> ProcessingResult
+where
+    I: IntoIterator<Item = &'a Rc<CvlMat>>,
+    I::IntoIter: Clone,
+{
+    let frame_images = frame_images.into_iter();
+    let base_frame = match frame_images.clone().next() {
+        Some(frame) => frame.frame(),
+        None => {
+            let msg = "At least one frame is required to compute temporal median.";
+            return Err(ProcessingError::GenDenoise(msg.to_string()));
         }
+    };
+
+    let is_compatible = frame_images.clone().all(|image| {
+        let frame = image.frame();
+        frame.typ() == CV_8U && frame.size().ok() == base_frame.size().ok()
+    });
+
+    if !is_compatible {
+        let msg = "Failed while trying to compute temporal median of incompatible frames.";
+        return Err(ProcessingError::GenDenoise(msg.to_string()));
+    }
+
+    match compute_temporal_median(frame_images) {
+        Ok(median) => Ok(CvlMat::from(median)),
+        Err(err) => Err(ProcessingError::opencv("temporal_median", base_frame, err)),
     }
 }
 
+fn compute_temporal_median<'a, I>(frame_images: I) -> opencv::Result<Mat>
+where
+    I: Iterator<Item = &'a Rc<CvlMat>>,
+{
+    let frames = frame_images
+        .map(|image| image.frame().try_clone())
+        .collect::<opencv::Result<Vec<Mat>>>()?;
+
+    let buffers = frames
+        .iter()
+        .map(|frame| frame.data_typed::<u8>())
+        .collect::<opencv::Result<Vec<&[u8]>>>()?;
+
+    let base_frame = &frames[0];
+    let mut median = Mat::zeros(base_frame.rows(), base_frame.cols(), CV_8U)?.to_mat()?;
+    let mut values = vec![0u8; buffers.len()];
+    let middle = buffers.len() / 2;
+    for (index, pixel) in median.data_typed_mut::<u8>()?.iter_mut().enumerate() {
+        values
+            .iter_mut()
+            .zip(buffers.iter())
+            .for_each(|(value, buffer)| *value = buffer[index]);
+        *pixel = *values.select_nth_unstable(middle).1;
+    }
+
+    Ok(median)
+}
+
 /// This method returns canny image from passed grayscale image by passed parameters.
 /// The Canny edge detector is an edge detection operator that uses a multi-stage algorithm
 /// to detect a wide range of edges in images. It was developed by John F. Canny in 1986.
@@ -101,7 +358,7 @@ pub fn gen_threshold_frame(frame: &CvlMat, thresh: f64, maxval: f64) -> Processi
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenCanny`](ProcessingError::GenCanny) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// transform passed image to canny image.
 #[inline(always)]
 pub fn gen_canny_frame(
@@ -114,23 +371,17 @@ pub fn gen_canny_frame(
     let mut canny_frame = Mat::default();
     match canny(frame.frame(), &mut canny_frame, low, high, size, is_l2) {
         Ok(_) => Ok(CvlMat::from(canny_frame)),
-        Err(_) => {
-            let msg = "Failed while trying to transform frame to canny.";
-            Err(ProcessingError::GenCanny(msg.to_string()))
-        }
+        Err(err) => Err(ProcessingError::opencv("canny", frame.frame(), err)),
     }
 }
 
-/// This method returns canny image from passed grayscale image by passed parameters.
-/// The Canny edge detector is an edge detection operator that uses a multi-stage algorithm
-/// to detect a wide range of edges in images. It was developed by John F. Canny in 1986.
-/// Canny also produced a computational theory of edge detection explaining why the
-/// technique works.
-///
-/// ![canny](/resources/canny.jpg "Example of Canny image")
+/// This method returns canny image from passed grayscale image with legacy automatic
+/// thresholds which are computed as `1 - sigma + mean` and `1 + sigma + mean` of frame
+/// intensity. It is kept to reproduce results of previous versions, use
+/// [`gen_auto_canny_frame`] for standard median-based thresholds.
 ///
 /// ## Parameters:
-/// * frame: (&CvlMat) the passed video stream frame to transform.
+/// * frame: (&CvlMat) the passed grayscale (CV_8UC1) video stream frame to transform.
 /// * size: (i32) the aperture size of Sobel operator to generate Canny view.
 /// * sigma: (f64) the value to vary the percentage thresholds that are determined based on simple statistics.
 /// * is_l2: (bool) the specifies the equation for finding gradient magnitude.
@@ -139,26 +390,59 @@ pub fn gen_canny_frame(
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenCanny`](ProcessingError::GenCanny) if failed while trying to
-/// transform passed image to canny image.
+/// Returns [`GenCanny`](ProcessingError::GenCanny) if failed while trying to compute mean of
+/// passed image and [`OpenCv`](ProcessingError::OpenCv) if failed while trying to transform
+/// it to canny image.
 #[inline(always)]
-pub fn gen_canny_frame_by_sigma(
+pub fn gen_canny_frame_by_mean_legacy(
     frame: &CvlMat,
     size: i32,
     sigma: f64,
     is_l2: bool,
 ) -> ProcessingResult {
-    let median = calculate_mat_median(frame).unwrap_or(0f64);
-    let (low, high) = (1f64 - sigma + median, 1f64 + &sigma + median);
+    let mean = calculate_mat_mean(frame).ok_or_else(|| {
+        let msg = "Failed while trying to compute frame mean.";
+        ProcessingError::GenCanny(msg.to_string())
+    })?;
 
-    let mut canny_frame = Mat::default();
-    match canny(frame.deref(), &mut canny_frame, low, high, size, is_l2) {
-        Ok(_) => Ok(CvlMat::from(canny_frame)),
-        Err(_) => {
-            let msg = "Failed while trying to transform frame to canny.";
-            Err(ProcessingError::GenCanny(msg.to_string()))
+    let (low, high) = (1f64 - sigma + mean, 1f64 + sigma + mean);
+    gen_canny_frame(frame, low, high, size, is_l2)
+}
+
+/// This method returns canny image from passed grayscale image with automatic thresholds.
+/// The thresholds are computed as `(1 - sigma) * median` and `(1 + sigma) * median` of frame
+/// intensity, so edges detection adapts to frame brightness.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale (CV_8UC1) video stream frame to transform.
+/// * size: (i32) the aperture size of Sobel operator to generate Canny view.
+/// * sigma: (f64) the relative deviation of thresholds from median (like 0.33).
+/// * is_l2: (bool) the specifies the equation for finding gradient magnitude.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`GenCanny`](ProcessingError::GenCanny) if failed while trying to compute median
+/// of passed image and [`OpenCv`](ProcessingError::OpenCv) if failed while trying to transform
+/// it to canny image.
+pub fn gen_auto_canny_frame(
+    frame: &CvlMat,
+    size: i32,
+    sigma: f64,
+    is_l2: bool,
+) -> ProcessingResult {
+    let median = match calculate_mat_median(frame) {
+        Some(median) => median,
+        None => {
+            let msg = "Failed while trying to compute frame median.";
+            return Err(ProcessingError::GenCanny(msg.to_string()));
         }
-    }
+    };
+
+    let low = ((1f64 - sigma) * median).clamp(0f64, 255f64);
+    let high = ((1f64 + sigma) * median).clamp(0f64, 255f64);
+    gen_canny_frame(frame, low, high, size, is_l2)
 }
 
 /// This method returns new Mat object with zeros by passed rows, columns and type parameters.
@@ -170,11 +454,10 @@ pub fn gen_canny_frame_by_sigma(
 /// * cv_type: (i32) a Mat type (like CV_64FC4).
 ///
 /// ## Returns:
-/// Returns `Option<Mat>` of executing [`Mat::zeros`] method from opencv library.
+/// Returns `opencv::Result<Mat>` of executing [`Mat::zeros`] method from opencv library.
 #[inline(always)]
-fn create_zeros_mat(rows: i32, cols: i32, cv_type: i32) -> Option<Mat> {
-    let zeros_frame = Mat::zeros(rows, cols, cv_type).unwrap();
-    zeros_frame.to_mat().ok()
+fn create_zeros_mat(rows: i32, cols: i32, cv_type: i32) -> opencv::Result<Mat> {
+    Mat::zeros(rows, cols, cv_type)?.to_mat()
 }
 
 /// There is wrapper for [Mat::roi] method which returns a sub-Mat object from source Mat and Rect.
@@ -204,24 +487,84 @@ fn create_roi_mat(frame: &Mat, row: i32, col: i32, window: i32) -> Option<Mat> {
 /// it helps distinguish it from other types of means, such as geometric and harmonic.
 ///
 /// ## Parameters:
-/// * frame: (&CvlMat) a passed video stream frame to transform.
+/// * frame: (&CvlMat) a passed grayscale (CV_8UC1) video stream frame.
+///
+/// ## Results:
+/// Returns `Some(f64)` on success, or `None` if passed image is empty or is not CV_8UC1.
+pub fn calculate_mat_mean(frame: &CvlMat) -> Option<f64> {
+    let histogram = compute_mat_histogram(frame.frame())?;
+    let total = histogram.iter().sum::<u64>();
+    let sum = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, count)| value as u64 * count)
+        .sum::<u64>();
+
+    Some(sum as f64 / total as f64)
+}
+
+/// This method returns median of all elements of passed 8-bit single-channel image. The median
+/// is computed by intensity histogram, so it is the lowest intensity value which is greater or
+/// equal than a half of image pixels.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) a passed grayscale (CV_8UC1) video stream frame.
 ///
 /// ## Results:
-/// Returns `Option<f64>` of executing [`Array::mean`] method from ndarray library.
+/// Returns `Some(f64)` on success, or `None` if passed image is empty or is not CV_8UC1.
 pub fn calculate_mat_median(frame: &CvlMat) -> Option<f64> {
-    let mat_frame = frame.frame();
-    let rows = mat_frame.rows() as usize;
-    let cols = mat_frame.cols() as usize;
+    calculate_mat_percentile(frame, 50f64)
+}
 
-    let buffer = frame
-        .frame()
-        .data_typed::<u8>()
-        .unwrap()
-        .iter()
-        .map(|d| *d as f64)
-        .collect();
+/// This method returns percentile of all elements of passed 8-bit single-channel image by
+/// intensity histogram.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) a passed grayscale (CV_8UC1) video stream frame.
+/// * percentile: (f64) a percentile value within `[0, 100]` range.
+///
+/// ## Results:
+/// Returns `Some(f64)` on success, or `None` if passed image is empty or is not CV_8UC1,
+/// or passed percentile is out of range.
+pub fn calculate_mat_percentile(frame: &CvlMat, percentile: f64) -> Option<f64> {
+    if !(0f64..=100f64).contains(&percentile) {
+        return None;
+    }
+
+    let histogram = compute_mat_histogram(frame.frame())?;
+    let total = histogram.iter().sum::<u64>();
+    let rank = ((percentile / 100f64) * total as f64).ceil().max(1f64) as u64;
+
+    let mut cumulative = 0u64;
+    for (value, count) in histogram.iter().enumerate() {
+        cumulative += count;
+        if cumulative >= rank {
+            return Some(value as f64);
+        }
+    }
+
+    None
+}
 
-    Array::from_shape_vec((rows, cols), buffer).unwrap().mean()
+fn compute_mat_histogram(frame: &Mat) -> Option<[u64; 256]> {
+    if frame.empty() || frame.typ() != CV_8U {
+        return None;
+    }
+
+    let continuous = match frame.is_continuous() {
+        true => None,
+        false => frame.try_clone().ok(),
+    };
+
+    let mut histogram = [0u64; 256];
+    let data = continuous
+        .as_ref()
+        .unwrap_or(frame)
+        .data_typed::<u8>()
+        .ok()?;
+    data.iter()
+        .for_each(|value| histogram[*value as usize] += 1);
+    Some(histogram)
 }
 
 /// This method returns distribution image from passed grayscale image by passed parameters.
@@ -239,24 +582,29 @@ pub fn calculate_mat_median(frame: &CvlMat) -> Option<f64> {
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenDistribution`](ProcessingError::GenDistribution) if failed while trying to
-/// transform passed image to distribution image.
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to transform passed
+/// image to distribution image.
 pub fn gen_distribution_frame(image: &CvlMat, thresh: f64, maxval: f64) -> ProcessingResult {
-    let mat_frame = image.frame();
-    let sobel_frame = gen_sobel_frame(mat_frame).unwrap();
-    let g_x = sobel_frame.frame().clone();
-    let g_y = sobel_frame.frame().clone();
+    let sobel_frame = gen_sobel_frame(image)?;
+
+    let g_x = sobel_frame.frame();
+    let g_y = sobel_frame.frame();
 
     let mut magnitude = Mat::default();
     let mut orientation = Mat::default();
-    cart_to_polar(&g_x, &g_y, &mut magnitude, &mut orientation, true).unwrap();
-
     let mut mask = Mat::default();
-    threshold(&magnitude, &mut mask, thresh, maxval, THRESH_BINARY).unwrap();
-
     let scalar = Scalar::new(0.0, 0.0, 0.0, 0.0);
-    let shape = (orientation.rows(), orientation.cols(), 3);
-    let img_map = Mat::new_rows_cols_with_default(shape.0, shape.1, CV_8UC3, scalar).unwrap();
+    let result = cart_to_polar(g_x, g_y, &mut magnitude, &mut orientation, true)
+        .and_then(|_| threshold(&magnitude, &mut mask, thresh, maxval, THRESH_BINARY))
+        .and_then(|_| {
+            let shape = (orientation.rows(), orientation.cols());
+            Mat::new_rows_cols_with_default(shape.0, shape.1, CV_8UC3, scalar)
+        });
+
+    let img_map = match result {
+        Ok(img_map) => img_map,
+        Err(err) => return Err(ProcessingError::opencv("cart_to_polar", g_x, err)),
+    };
 
     // let mut nonzero_mask = VectorOfMat::default();
     // println!("{} {}", mat_frame.channels(), mat_frame.dims());
@@ -282,23 +630,278 @@ pub fn gen_distribution_frame(image: &CvlMat, thresh: f64, maxval: f64) -> Proce
 /// The first case corresponds to a kernel of:
 ///
 /// ## Parameters:
-/// * frame: (&Mat) the passed video stream frame to transform.
+/// * frame: (&CvlMat) the passed video stream frame to transform.
 ///
 /// ## Returns:
 /// Returns `Ok(CvlMat)` of executing [`sobel`] method of opencv library.
 ///
 /// ## Errors:
-/// Returns [`GenSobel`](ProcessingError::GenSobel) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// transform passed image to distribution image.
 #[inline(always)]
-fn gen_sobel_frame(frame: &Mat) -> ProcessingResult {
+pub fn gen_sobel_frame(frame: &CvlMat) -> ProcessingResult {
     let mut g_x = Mat::default();
-    match sobel(frame, &mut g_x, CV_32F, 1, 0, 3, 1.0, 0f64, BORDER_DEFAULT) {
+    match sobel(
+        frame.frame(),
+        &mut g_x,
+        CV_32F,
+        1,
+        0,
+        3,
+        1.0,
+        0f64,
+        BORDER_DEFAULT,
+    ) {
         Ok(_) => Ok(CvlMat::new(g_x.to_owned())),
-        Err(_) => {
-            let msg = "Failed while trying to transform frame to sobel.";
-            Err(ProcessingError::GenSobel(msg.to_string()))
-        }
+        Err(err) => Err(ProcessingError::opencv("sobel", frame.frame(), err)),
+    }
+}
+
+/// This method returns gradient magnitude image of passed grayscale image computed by x- and
+/// y- Sobel derivatives. The magnitude is saturated to CV_8U, so it may be used instead of
+/// Canny image within chain.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale video stream frame to transform.
+/// * ksize: (i32) the aperture size of Sobel operator (1, 3, 5 or 7).
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// compute Sobel derivatives of passed image.
+pub fn gen_sobel_magnitude_frame(frame: &CvlMat, ksize: i32) -> ProcessingResult {
+    let compute = |dx: i32, dy: i32, result: &mut Mat| {
+        sobel(
+            frame.frame(),
+            result,
+            CV_32F,
+            dx,
+            dy,
+            ksize,
+            1.0,
+            0f64,
+            BORDER_DEFAULT,
+        )
+    };
+
+    let (mut g_x, mut g_y) = (Mat::default(), Mat::default());
+    match compute(1, 0, &mut g_x).and_then(|_| compute(0, 1, &mut g_y)) {
+        Ok(_) => gen_gradient_magnitude_frame(&g_x, &g_y),
+        Err(err) => Err(ProcessingError::opencv("sobel", frame.frame(), err)),
+    }
+}
+
+/// This method returns gradient magnitude image of passed grayscale image computed by x- and
+/// y- Scharr derivatives. The Scharr operator is more accurate than Sobel 3x3 operator for
+/// rotation-invariant gradients.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale video stream frame to transform.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// compute Scharr derivatives of passed image.
+pub fn gen_scharr_frame(frame: &CvlMat) -> ProcessingResult {
+    let compute = |dx: i32, dy: i32, result: &mut Mat| {
+        scharr(
+            frame.frame(),
+            result,
+            CV_32F,
+            dx,
+            dy,
+            1.0,
+            0f64,
+            BORDER_DEFAULT,
+        )
+    };
+
+    let (mut g_x, mut g_y) = (Mat::default(), Mat::default());
+    match compute(1, 0, &mut g_x).and_then(|_| compute(0, 1, &mut g_y)) {
+        Ok(_) => gen_gradient_magnitude_frame(&g_x, &g_y),
+        Err(err) => Err(ProcessingError::opencv("scharr", frame.frame(), err)),
+    }
+}
+
+/// This method returns absolute Laplacian image of passed grayscale image saturated to CV_8U.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) the passed grayscale video stream frame to transform.
+/// * ksize: (i32) the aperture size to compute second-derivative filters (positive and odd).
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// compute Laplacian of passed image.
+pub fn gen_laplacian_frame(frame: &CvlMat, ksize: i32) -> ProcessingResult {
+    let mut lap_frame = Mat::default();
+    let mut abs_frame = Mat::default();
+    let result = laplacian(
+        frame.frame(),
+        &mut lap_frame,
+        CV_16S,
+        ksize,
+        1.0,
+        0f64,
+        BORDER_DEFAULT,
+    )
+    .and_then(|_| convert_scale_abs(&lap_frame, &mut abs_frame, 1.0, 0f64));
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(abs_frame)),
+        Err(err) => Err(ProcessingError::opencv("laplacian", frame.frame(), err)),
+    }
+}
+
+fn gen_gradient_magnitude_frame(g_x: &Mat, g_y: &Mat) -> ProcessingResult {
+    let mut magnitude_frame = Mat::default();
+    let mut abs_frame = Mat::default();
+    let result = magnitude(g_x, g_y, &mut magnitude_frame)
+        .and_then(|_| convert_scale_abs(&magnitude_frame, &mut abs_frame, 1.0, 0f64));
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(abs_frame)),
+        Err(err) => Err(ProcessingError::opencv("magnitude", g_x, err)),
+    }
+}
+
+/// This method returns global motion of passed frame relative to reference frame. Small global
+/// shifts (camera vibration, subject swaying) make every edge of Canny image light up in the
+/// difference image, so frames should be aligned to reference frame before differencing.
+/// The motion may be estimated over the whole frame or within passed region only.
+///
+/// ## Parameters:
+/// * reference: (&CvlMat) a single-channel reference frame.
+/// * frame: (&CvlMat) a single-channel frame of the same size to estimate motion.
+/// * method: (Stabilization) a method to estimate motion.
+/// * region: (`Option<Rect>`) a region of frames to estimate motion.
+///
+/// ## Returns:
+/// Returns `Ok(Motion)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to estimate motion
+/// between passed frames.
+pub fn estimate_frame_motion(
+    reference: &CvlMat,
+    frame: &CvlMat,
+    method: Stabilization,
+    region: Option<Rect>,
+) -> Result<Motion, ProcessingError> {
+    let (reference, frame) = (reference.frame(), frame.frame());
+    let result = match method {
+        Stabilization::None => Ok(Motion::default()),
+        Stabilization::PhaseCorrelation => estimate_phase_motion(reference, frame, region),
+        Stabilization::Ecc => estimate_ecc_motion(reference, frame, region),
+    };
+
+    result.map_err(|err| ProcessingError::opencv("estimate_frame_motion", frame, err))
+}
+
+/// Converts passed frame (or region of frame) to CV_32F type used by motion estimators.
+fn prepare_motion_frame(frame: &Mat, region: Option<Rect>) -> opencv::Result<Mat> {
+    let mut converted = Mat::default();
+    match region {
+        None => frame.convert_to(&mut converted, CV_32F, 1f64, 0f64)?,
+        Some(rect) => Mat::roi(frame, rect)?.convert_to(&mut converted, CV_32F, 1f64, 0f64)?,
+    };
+
+    Ok(converted)
+}
+
+fn estimate_phase_motion(
+    reference: &Mat,
+    frame: &Mat,
+    region: Option<Rect>,
+) -> opencv::Result<Motion> {
+    let reference = prepare_motion_frame(reference, region)?;
+    let frame = prepare_motion_frame(frame, region)?;
+
+    let mut response = 0f64;
+    let shift = phase_correlate(&reference, &frame, &Mat::default(), &mut response)?;
+    Ok(Motion::new(shift.x, shift.y, 0f64, response))
+}
+
+fn estimate_ecc_motion(
+    reference: &Mat,
+    frame: &Mat,
+    region: Option<Rect>,
+) -> opencv::Result<Motion> {
+    let reference = prepare_motion_frame(reference, region)?;
+    let frame = prepare_motion_frame(frame, region)?;
+
+    let mut warp = Mat::eye(2, 3, CV_32F)?.to_mat()?;
+    let criteria_type = TermCriteria_COUNT + TermCriteria_EPS;
+    let criteria = TermCriteria::new(criteria_type, ECC_MAX_ITERATIONS, ECC_EPSILON)?;
+    let response = find_transform_ecc(
+        &reference,
+        &frame,
+        &mut warp,
+        MOTION_EUCLIDEAN,
+        criteria,
+        &Mat::default(),
+        ECC_GAUSS_FILTER_SIZE,
+    )?;
+
+    let cos = *warp.at_2d::<f32>(0, 0)? as f64;
+    let sin = *warp.at_2d::<f32>(1, 0)? as f64;
+    let mut dx = *warp.at_2d::<f32>(0, 2)? as f64;
+    let mut dy = *warp.at_2d::<f32>(1, 2)? as f64;
+
+    // The rotation has been estimated around region origin, so translation is moved
+    // to rotation around frame origin.
+    if let Some(rect) = region {
+        let (ox, oy) = (rect.x as f64, rect.y as f64);
+        dx += ox - (cos * ox - sin * oy);
+        dy += oy - (sin * ox + cos * oy);
+    }
+
+    let angle = sin.atan2(cos).to_degrees();
+    Ok(Motion::new(dx, dy, angle, response))
+}
+
+/// This method returns passed frame aligned to reference frame by compensation of passed
+/// motion (see [`estimate_frame_motion`]). The pixels out of frame bounds are replicated
+/// from the frame border, so there are no false edges on the stabilized frame.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) a passed video stream frame to stabilize.
+/// * motion: (&Motion) a motion of frame relative to reference frame.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// warp passed frame.
+pub fn gen_stabilized_frame(frame: &CvlMat, motion: &Motion) -> ProcessingResult {
+    let mat_frame = frame.frame();
+    let size = Size::new(mat_frame.cols(), mat_frame.rows());
+    let flags = INTER_LINEAR | WARP_INVERSE_MAP;
+
+    let mut stabilized = Mat::default();
+    let result = motion.to_affine().and_then(|affine| {
+        let border_value = Scalar::default();
+        warp_affine(
+            mat_frame,
+            &mut stabilized,
+            &affine,
+            size,
+            flags,
+            BORDER_REPLICATE,
+            border_value,
+        )
+    });
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(stabilized)),
+        Err(err) => Err(ProcessingError::opencv("warp_affine", mat_frame, err)),
     }
 }
 
@@ -312,17 +915,14 @@ fn gen_sobel_frame(frame: &Mat) -> ProcessingResult {
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenDifferences`](ProcessingError::GenDifferences) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// execute [`absdiff`] method for passed images.
 #[inline]
 fn gen_diff_frame(img1: &Mat, img2: &Mat) -> ProcessingResult {
     let mut tmp = Mat::default();
     match absdiff(img1, img2, &mut tmp) {
         Ok(_) => Ok(CvlMat::from(tmp)),
-        Err(_) => {
-            let msg = "Failed while trying to execute absdiff function.";
-            Err(ProcessingError::GenDifferences(msg.to_string()))
-        }
+        Err(err) => Err(ProcessingError::opencv("absdiff", img1, err)),
     }
 }
 
@@ -340,28 +940,39 @@ fn gen_diff_frame(img1: &Mat, img2: &Mat) -> ProcessingResult {
 ///     0 1 0       0 1 0      0 0 0
 ///
 /// ## Parameters:
-/// * frame_images: (&[`Rc<CvlMat>`]) a list of video stream frames to get difference-image;
+/// * frame_images: (I) a list (slice, vector or [`CvlMatDeque`](crate::core::deque::CvlMatDeque))
+/// of video stream frames to get difference-image;
 ///
 /// ## Returns:
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenAbs`](ProcessingError::GenAbs) if failed while trying to generate difference
-/// image from passed set of canny images.
-pub fn gen_abs_frame(frame_images: &[Rc<CvlMat>]) -> ProcessingResult {
-    if frame_images.len() <= 1 {
-        let frame = frame_images.first().unwrap();
-        let own_frame = frame.as_ref().to_owned();
-        return Ok(own_frame);
-    }
-
-    let base_image = frame_images.last().unwrap();
-    let sliced_array = &frame_images[0..frame_images.len() - 1];
-    let differences: Vec<Rc<CvlMat>> = sliced_array
-        .iter()
-        .map(|m| gen_diff_frame(base_image.frame(), m.frame()).unwrap())
-        .map(Rc::new)
-        .collect();
+/// Returns [`NotEnoughFrames`](ProcessingError::NotEnoughFrames) if passed list is empty and
+/// [`OpenCv`](ProcessingError::OpenCv) if failed while trying to generate difference image
+/// from passed set of canny images.
+pub fn gen_abs_frame<'a, I>(frame_images: I) -> ProcessingResult
+where
+    I: IntoIterator<Item = &'a Rc<CvlMat>>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    let mut frame_images = frame_images.into_iter();
+    let base_image = match frame_images.next_back() {
+        Some(image) => image,
+        None => {
+            return Err(ProcessingError::NotEnoughFrames {
+                required: 1,
+                available: 0,
+            })
+        }
+    };
+
+    let differences = frame_images
+        .map(|m| gen_diff_frame(base_image.frame(), m.frame()).map(Rc::new))
+        .collect::<Result<Vec<Rc<CvlMat>>, ProcessingError>>()?;
+
+    if differences.is_empty() {
+        return Ok(base_image.as_ref().to_owned());
+    }
 
     let result_image = gen_abs_frame(&differences)?;
     Ok(result_image)
@@ -381,24 +992,239 @@ pub fn gen_abs_frame(frame_images: &[Rc<CvlMat>]) -> ProcessingResult {
 ///     0 1 0       0 1 0      0 0 0
 ///
 /// ## Parameters:
-/// * frame_images: (&[`Rc<CvlMat>`]) a list of video stream frames to get difference-image;
+/// * frame_images: (I) a list (slice, vector or [`CvlMatDeque`](crate::core::deque::CvlMatDeque))
+/// of video stream frames to get difference-image;
 ///
 /// ## Returns:
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`GenAbs`](ProcessingError::GenAbs) if failed while trying to generate difference
-/// image from passed set of canny images.
-pub fn gen_abs_frame_reduce(frame_images: &[Rc<CvlMat>]) -> ProcessingResult {
-    let result = frame_images
-        .iter()
-        .cloned()
-        .reduce(|img1, img2| Rc::new(gen_diff_frame(img1.frame(), img2.frame()).unwrap()));
+/// Returns [`NotEnoughFrames`](ProcessingError::NotEnoughFrames) if passed list is empty and
+/// [`OpenCv`](ProcessingError::OpenCv) if failed while trying to generate difference image
+/// from passed set of canny images.
+pub fn gen_abs_frame_reduce<'a, I>(frame_images: I) -> ProcessingResult
+where
+    I: IntoIterator<Item = &'a Rc<CvlMat>>,
+{
+    let mut frame_images = frame_images.into_iter();
+    let first_image = match frame_images.next() {
+        Some(image) => image,
+        None => {
+            return Err(ProcessingError::NotEnoughFrames {
+                required: 1,
+                available: 0,
+            })
+        }
+    };
+
+    frame_images.try_fold(first_image.as_ref().to_owned(), |img1, img2| {
+        gen_diff_frame(img1.frame(), img2.frame())
+    })
+}
+
+/// This method returns amplitude image by passed list of followed one by one frames of video
+/// stream. Unlike the difference image, which marks only the fact of an edge hit, each pixel of
+/// the amplitude image keeps the average of absolute inter-frame intensity differences over all
+/// passed frames. The result is a floating-point (CV_32FC1) map within [0, 255] range.
+///
+/// ## Parameters:
+/// * frame_images: (I) a list (slice, vector or [`CvlMatDeque`](crate::core::deque::CvlMatDeque))
+/// of single-channel video stream frames;
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`NotEnoughFrames`](ProcessingError::NotEnoughFrames) if less than two frames have
+/// been passed and [`OpenCv`](ProcessingError::OpenCv) if failed while trying to compute
+/// differences of passed frames.
+pub fn gen_amplitude_frame<'a, I>(frame_images: I) -> ProcessingResult
+where
+    I: IntoIterator<Item = &'a Rc<CvlMat>>,
+{
+    let mut frame_images = frame_images.into_iter().peekable();
+    let base_image = match frame_images.next() {
+        Some(image) if frame_images.peek().is_some() => image,
+        other => {
+            return Err(ProcessingError::NotEnoughFrames {
+                required: 2,
+                available: usize::from(other.is_some()),
+            })
+        }
+    };
+
+    match accumulate_amplitude(base_image, frame_images) {
+        Ok(amplitude) => Ok(CvlMat::from(amplitude)),
+        Err(err) => Err(ProcessingError::opencv(
+            "accumulate_amplitude",
+            base_image.frame(),
+            err,
+        )),
+    }
+}
+
+/// Sums absolute differences of each pair of neighbouring frames into CV_32F accumulator
+/// and returns average value of these differences for each pixel.
+fn accumulate_amplitude<'a, I>(base_image: &'a Rc<CvlMat>, frame_images: I) -> opencv::Result<Mat>
+where
+    I: Iterator<Item = &'a Rc<CvlMat>>,
+{
+    let base_frame = base_image.frame();
+    let mut accumulated = Mat::zeros(base_frame.rows(), base_frame.cols(), CV_32F)?.to_mat()?;
+    let (mut previous, mut pairs_count) = (base_frame, 0usize);
+    for image in frame_images {
+        let mut diff = Mat::default();
+        absdiff(previous, image.frame(), &mut diff)?;
+        accumulate(&diff, &mut accumulated, &Mat::default())?;
+        previous = image.frame();
+        pairs_count += 1;
+    }
+
+    let mut amplitude = Mat::default();
+    let scale = 1f64 / pairs_count as f64;
+    accumulated.convert_to(&mut amplitude, CV_32F, scale, 0f64)?;
+    Ok(amplitude)
+}
+
+/// This method returns amplitude image accumulated with exponential decay. Each pixel of result
+/// image is computed as `decay * accumulated + (1 - decay) * amplitude`, so the bigger decay
+/// value the longer vibration of pixel stays visible. If passed images have different sizes
+/// (for example, stream resolution has been changed) the current amplitude image is returned.
+///
+/// ## Parameters:
+/// * accumulated: (&CvlMat) a previously accumulated amplitude image.
+/// * amplitude: (&CvlMat) a current amplitude image (see [`gen_amplitude_frame`]).
+/// * decay: (f64) a decay factor within [0, 1] range.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// accumulate passed amplitude images.
+pub fn gen_accumulated_amplitude_frame(
+    accumulated: &CvlMat,
+    amplitude: &CvlMat,
+    decay: f64,
+) -> ProcessingResult {
+    let is_same_size = accumulated.rows() == amplitude.rows()
+        && accumulated.columns() == amplitude.columns()
+        && accumulated.typ() == amplitude.typ();
+
+    if !is_same_size {
+        return Ok(amplitude.to_owned());
+    }
+
+    let alpha = decay.clamp(0f64, 1f64);
+    let (acc_frame, amp_frame) = (accumulated.frame(), amplitude.frame());
+
+    let mut result = Mat::default();
+    match add_weighted(
+        acc_frame,
+        alpha,
+        amp_frame,
+        1f64 - alpha,
+        0f64,
+        &mut result,
+        -1,
+    ) {
+        Ok(_) => Ok(CvlMat::from(result)),
+        Err(err) => Err(ProcessingError::opencv(
+            "add_weighted",
+            accumulated.frame(),
+            err,
+        )),
+    }
+}
+
+/// This method returns colorized (CV_8UC3) image of passed amplitude image. The amplitude values
+/// are saturated to [0, 255] range without per-frame normalization, so the same color always
+/// means the same amplitude between frames.
+///
+/// ## Parameters:
+/// * amplitude: (&CvlMat) an amplitude image (see [`gen_amplitude_frame`]).
+/// * colormap: (i32) an opencv colormap type (like COLORMAP_JET).
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// apply colormap to passed amplitude image.
+pub fn gen_colored_amplitude_frame(amplitude: &CvlMat, colormap: i32) -> ProcessingResult {
+    let mut amplitude_u8 = Mat::default();
+    let mut colored = Mat::default();
+    let result = amplitude
+        .frame()
+        .convert_to(&mut amplitude_u8, CV_8U, 1f64, 0f64)
+        .and_then(|_| apply_color_map(&amplitude_u8, &mut colored, colormap));
 
     match result {
-        None => Err(ProcessingError::GenAbs),
-        Some(frame) => Ok(frame.as_ref().to_owned()),
+        Ok(_) => Ok(CvlMat::from(colored)),
+        Err(err) => Err(ProcessingError::opencv(
+            "apply_color_map",
+            amplitude.frame(),
+            err,
+        )),
+    }
+}
+
+/// This method returns map of neighbours count (CV_32SC1) by passed difference image. Each
+/// nonzero pixel of passed image is replaced by amount of nonzero pixels around it (the same
+/// value which is used by [`compute_vibration`] to choose pixel color), pixels with less than
+/// `neighbours` nonzero neighbours are set to zero. The result map may be rendered by
+/// [`render_map`](crate::ui::render::render_map) with any colormap.
+///
+/// ## Parameters:
+/// * image: (&CvlMat) a passed diff-image (results of abs) to transform.
+/// * neighbours: (i32) a neighbours count value to filter noise of vibration.
+/// * window_size: (i32) a offset from central pixel to compute non-null pixel neighbours.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
+/// compute neighbours of difference image pixels.
+pub fn compute_neighbours_map(
+    image: &CvlMat,
+    neighbours: i32,
+    window_size: i32,
+) -> ProcessingResult {
+    let frame_mat = image.frame();
+    let mut non_zero_pixels = Vector::<Point>::new();
+    let mut result_frame = match create_zeros_mat(frame_mat.rows(), frame_mat.cols(), CV_32S)
+        .and_then(|zeros| find_non_zero(frame_mat, &mut non_zero_pixels).map(|_| zeros))
+    {
+        Ok(zeros) => zeros,
+        Err(err) => return Err(ProcessingError::opencv("find_non_zero", frame_mat, err)),
+    };
+
+    for non_zero_point in non_zero_pixels.to_vec() {
+        let (row, col) = (non_zero_point.y, non_zero_point.x);
+        let non_zero_count = match count_pixel_neighbours(frame_mat, row, col, window_size) {
+            Some(count) if count >= neighbours => count,
+            _ => continue,
+        };
+
+        if let Ok(pixel) = result_frame.at_2d_mut::<i32>(row, col) {
+            *pixel = non_zero_count;
+        }
+    }
+
+    Ok(CvlMat::from(result_frame))
+}
+
+/// Returns amount of nonzero pixels within window around pixel (row, col) or `None` if window
+/// is out of frame bounds.
+#[inline(always)]
+fn count_pixel_neighbours(frame: &Mat, row: i32, col: i32, window: i32) -> Option<i32> {
+    if row == 0 || col == 0 {
+        return None;
     }
+
+    let roi_mat = create_roi_mat(frame, row, col, window)?;
+    count_non_zero(&roi_mat).ok()
 }
 
 /// This method returns image with vibrating pixels (colored by bounds values) by passed image.
@@ -419,7 +1245,7 @@ pub fn gen_abs_frame_reduce(frame_images: &[Rc<CvlMat>]) -> ProcessingResult {
 /// Returns `Ok(CvlMat)` on success, otherwise returns an error.
 ///
 /// ## Errors:
-/// Returns [`ComputeVibration`](ProcessingError::ComputeVibration) if failed while trying to
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to
 /// transform difference image to vibration image.
 pub fn compute_vibration(
     image: &CvlMat,
@@ -428,28 +1254,24 @@ pub fn compute_vibration(
     color_bounds: &ColorBounds,
 ) -> ProcessingResult {
     let frame_mat = image.frame();
-    let mut statistic = Statistic::default();
-    let mut result_frame = create_zeros_mat(frame_mat.rows(), frame_mat.cols(), CV_64FC4).unwrap();
-
+    let mut statistic = Statistic {
+        area: (frame_mat.rows() * frame_mat.cols()) as u32,
+        ..Statistic::default()
+    };
     let mut non_zero_pixels = Vector::<Point>::new();
-    find_non_zero(frame_mat, &mut non_zero_pixels).unwrap();
+    let mut result_frame = match create_zeros_mat(frame_mat.rows(), frame_mat.cols(), CV_64FC4)
+        .and_then(|zeros| find_non_zero(frame_mat, &mut non_zero_pixels).map(|_| zeros))
+    {
+        Ok(zeros) => zeros,
+        Err(err) => return Err(ProcessingError::opencv("find_non_zero", frame_mat, err)),
+    };
 
     for non_zero_point in non_zero_pixels.to_vec() {
         let (row, col) = (non_zero_point.y, non_zero_point.x);
-        if row == 0 || col == 0 {
-            continue;
-        }
-
-        let roi_mat = create_roi_mat(frame_mat, row, col, window_size);
-        if roi_mat.is_none() {
-            continue;
-        }
-
-        let roi_matrix = &roi_mat.unwrap();
-        let non_zero_count = count_non_zero(roi_matrix).unwrap();
-        if non_zero_count < neighbours {
-            continue;
-        }
+        let non_zero_count = match count_pixel_neighbours(frame_mat, row, col, window_size) {
+            Some(count) if count >= neighbours => count,
+            _ => continue,
+        };
 
         let colored_scalar = match non_zero_count {
             val if val >= color_bounds.get(4) => {
@@ -471,10 +1293,9 @@ pub fn compute_vibration(
             _ => Scalar::from(BLACK_COLOR),
         };
 
-        result_frame
-            .at_2d_mut::<Scalar>(row, col)
-            .unwrap()
-            .copy_from_slice(colored_scalar.as_slice());
+        if let Ok(pixel) = result_frame.at_2d_mut::<Scalar>(row, col) {
+            pixel.copy_from_slice(colored_scalar.as_slice());
+        }
     }
 
     let mut cvlmat = CvlMat::from(result_frame);
@@ -483,40 +1304,147 @@ pub fn compute_vibration(
     Ok(cvlmat)
 }
 
+/// This method returns image with vibrating pixels computed only within region of interest.
+/// The difference image is masked by passed mask before computing vibration, so pixels out of
+/// region and their neighbours are not counted. The area of returned statistic is equal to
+/// amount of nonzero pixels of mask.
+///
+/// ## Parameters:
+/// * image: (&CvlMat) a passed diff-image (results of abs) to transform.
+/// * mask: (&CvlMat) a binary mask of region of interest (see [`RegionOfInterest::mask`](crate::core::roi::RegionOfInterest::mask)).
+/// * neighbours: (i32) a neighbours count value to filter noise of vibration.
+/// * window_size: (i32) a offset from central pixel to compute non-null pixel neighbours.
+/// * color_bounds: (&ColorBounds) a object with channels values to set color for pixels.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to apply mask, to count
+/// mask area or to transform difference image to vibration image.
+pub fn compute_vibration_masked(
+    image: &CvlMat,
+    mask: &CvlMat,
+    neighbours: i32,
+    window_size: i32,
+    color_bounds: &ColorBounds,
+) -> ProcessingResult {
+    let masked = gen_masked_frame(image, mask)?;
+    let mut result = compute_vibration(&masked, neighbours, window_size, color_bounds)?;
+
+    let area = match count_non_zero(mask.frame()) {
+        Ok(area) => area as u32,
+        Err(err) => return Err(ProcessingError::opencv("count_non_zero", mask.frame(), err)),
+    };
+    let mut statistic = result.statistic().cloned().unwrap_or_default();
+    statistic.area = area;
+    result.set_statistic(statistic);
+
+    Ok(result)
+}
+
+/// This method returns copy of passed frame where all pixels out of passed mask are zero.
+///
+/// ## Parameters:
+/// * frame: (&CvlMat) a passed video stream frame to mask.
+/// * mask: (&CvlMat) a binary mask (CV_8UC1) of the same size.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to create result frame or
+/// to apply mask to frame.
+pub fn gen_masked_frame(frame: &CvlMat, mask: &CvlMat) -> ProcessingResult {
+    let mat_frame = frame.frame();
+    let result = Mat::zeros(mat_frame.rows(), mat_frame.cols(), mat_frame.typ())
+        .and_then(|zeros| zeros.to_mat())
+        .and_then(|mut masked| {
+            mat_frame.copy_to_masked(&mut masked, mask.frame())?;
+            Ok(masked)
+        });
+
+    match result {
+        Ok(masked) => Ok(CvlMat::from(masked)),
+        Err(err) => Err(ProcessingError::opencv("copy_to_masked", mat_frame, err)),
+    }
+}
+
+/// This method returns dispersion of statistic channels values from medians of history.
+///
+/// ## Parameters:
+/// * history_stats: (`Vec<&Statistic>`) a history of statistic values.
+/// * normalization: (f32) a value to divide dispersion values.
 ///
+/// ## Returns:
+/// Returns [`Dispersion`] of passed statistic history.
 pub fn compute_statistic(history_stats: Vec<&Statistic>, normalization: f32) -> Dispersion {
-    let stats_arrays: Vec<_> = history_stats
-        .into_iter()
-        .map(|st| [st.ch1, st.ch2, st.ch3, st.ch4])
-        .map(|sl| Array1::from_shape_vec(CHANNELS_COUNT, sl.to_vec()).unwrap())
-        .collect();
+    let stats_values = history_stats
+        .iter()
+        .map(|st| [st.ch1, st.ch2, st.ch3, st.ch4].map(f32::from))
+        .collect::<Vec<[f32; CHANNELS_COUNT]>>();
 
-    let stats_medians: &Vec<u16> = &stats_arrays
+    let stats_medians = history_stats
         .iter()
-        .map(Array::mean)
-        .map(Option::unwrap)
-        .collect();
+        .map(|st| {
+            [st.ch1, st.ch2, st.ch3, st.ch4]
+                .map(u32::from)
+                .iter()
+                .sum::<u32>()
+        })
+        .map(|sum| (sum / CHANNELS_COUNT as u32) as f32)
+        .collect::<Vec<f32>>();
+
+    compute_dispersion(&stats_values, &stats_medians, normalization)
+}
 
+/// This method returns dispersion of statistic computed over percentages of processed area
+/// (see [`Statistic::density`]) instead of absolute pixels counts. The dispersion is computed
+/// by the same formula as [`compute_statistic`], so statistic of regions of interest which
+/// have different area is comparable with statistic of the whole frame.
+///
+/// ## Parameters:
+/// * history_stats: (`Vec<&Statistic>`) a history of statistic values.
+/// * normalization: (f32) a value to divide dispersion values.
+///
+/// ## Returns:
+/// Returns [`Dispersion`] of passed statistic history.
+pub fn compute_density_statistic(history_stats: Vec<&Statistic>, normalization: f32) -> Dispersion {
+    let stats_values = history_stats
+        .iter()
+        .map(|st| st.density())
+        .collect::<Vec<[f32; CHANNELS_COUNT]>>();
+
+    let stats_medians = stats_values
+        .iter()
+        .map(|values| values.iter().sum::<f32>() / CHANNELS_COUNT as f32)
+        .collect::<Vec<f32>>();
+
+    compute_dispersion(&stats_values, &stats_medians, normalization)
+}
+
+/// Returns normalized square root of accumulated squared deviations of values from medians.
+fn compute_dispersion(
+    stats_values: &[[f32; CHANNELS_COUNT]],
+    stats_medians: &[f32],
+    normalization: f32,
+) -> Dispersion {
     let mut tmp_slice = [0f32; CHANNELS_COUNT];
-    stats_arrays.into_iter().for_each(|array| {
-        compute_math_expectation(&mut tmp_slice, &array, stats_medians);
+    stats_values.iter().for_each(|values| {
+        compute_math_expectation(&mut tmp_slice, values, stats_medians);
     });
 
-    Dispersion::from(
-        tmp_slice
-            .into_iter()
-            .map(f32::sqrt)
-            .map(|val| val / normalization)
-            .collect::<Vec<f32>>(),
-    )
+    let [ch1, ch2, ch3, ch4] = tmp_slice.map(|val| val.sqrt() / normalization);
+    Dispersion::new(ch1, ch2, ch3, ch4)
 }
 
-///
-fn compute_math_expectation(tmp_slice: &mut [f32; 4], array: &Array1<u16>, medians: &[u16]) {
-    (0..CHANNELS_COUNT).for_each(|index| {
-        let carr = *array.get(index).unwrap() as i32;
-        let cmed = *medians.get(index).unwrap() as i32;
-        let diff = (carr - cmed).pow(POW_DIFF_VALUE) as f32;
-        *tmp_slice.get_mut(index).unwrap() += diff;
-    });
+/// Accumulates squared differences between channels values and medians into passed slice.
+fn compute_math_expectation(tmp_slice: &mut [f32; 4], values: &[f32; 4], medians: &[f32]) {
+    tmp_slice
+        .iter_mut()
+        .zip(values.iter().zip(medians.iter()))
+        .for_each(|(acc, (cval, cmed))| {
+            let diff = (cval - cmed).powi(POW_DIFF_VALUE as i32);
+            *acc += diff;
+        });
 }
@@ -0,0 +1,398 @@
+use crate::core::bounds::ColorBounds;
+use crate::core::mat::CvlMat;
+use crate::errors::{ProcessingError, ProcessingResult};
+use opencv::core::{add_weighted, compare, hconcat2, Mat, Point, Scalar, Vec3b, VecN};
+use opencv::core::{CMP_EQ, CMP_GT, CV_64FC4, CV_8U, CV_8UC3};
+use opencv::imgproc::{apply_color_map, apply_color_map_user, cvt_color, line, put_text};
+use opencv::imgproc::{COLOR_BGR2GRAY, COLOR_BGRA2BGR, COLOR_GRAY2BGR};
+use opencv::imgproc::{FONT_HERSHEY_SIMPLEX, LINE_8};
+use opencv::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A maximum value of amplitude map pixel (see [`gen_amplitude_frame`](crate::gen_amplitude_frame)).
+pub const AMPLITUDE_MAX_VALUE: f64 = 255.0;
+
+const LUT_SIZE: usize = 256;
+const COLORBAR_WIDTH: i32 = 20;
+const LABELS_WIDTH: i32 = 40;
+
+/// A colormap used to render neighbours count or amplitude maps. All variants except of
+/// [`Custom`](Colormap::Custom) are wrappers for opencv colormaps, the custom colormap is a
+/// lookup table of 256 BGR colors.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Colormap {
+    Autumn,
+    Bone,
+    #[default]
+    Jet,
+    Winter,
+    Rainbow,
+    Ocean,
+    Summer,
+    Spring,
+    Cool,
+    Hsv,
+    Pink,
+    Hot,
+    Parula,
+    Magma,
+    Inferno,
+    Plasma,
+    Viridis,
+    Cividis,
+    Twilight,
+    TwilightShifted,
+    Turbo,
+    Custom(Vec<[u8; 3]>),
+}
+
+impl Colormap {
+    /// Returns opencv colormap type (like COLORMAP_JET) or `None` for custom colormap.
+    pub fn cv_type(&self) -> Option<i32> {
+        use opencv::imgproc::*;
+        let cv_type = match self {
+            Colormap::Autumn => COLORMAP_AUTUMN,
+            Colormap::Bone => COLORMAP_BONE,
+            Colormap::Jet => COLORMAP_JET,
+            Colormap::Winter => COLORMAP_WINTER,
+            Colormap::Rainbow => COLORMAP_RAINBOW,
+            Colormap::Ocean => COLORMAP_OCEAN,
+            Colormap::Summer => COLORMAP_SUMMER,
+            Colormap::Spring => COLORMAP_SPRING,
+            Colormap::Cool => COLORMAP_COOL,
+            Colormap::Hsv => COLORMAP_HSV,
+            Colormap::Pink => COLORMAP_PINK,
+            Colormap::Hot => COLORMAP_HOT,
+            Colormap::Parula => COLORMAP_PARULA,
+            Colormap::Magma => COLORMAP_MAGMA,
+            Colormap::Inferno => COLORMAP_INFERNO,
+            Colormap::Plasma => COLORMAP_PLASMA,
+            Colormap::Viridis => COLORMAP_VIRIDIS,
+            Colormap::Cividis => COLORMAP_CIVIDIS,
+            Colormap::Twilight => COLORMAP_TWILIGHT,
+            Colormap::TwilightShifted => COLORMAP_TWILIGHT_SHIFTED,
+            Colormap::Turbo => COLORMAP_TURBO,
+            Colormap::Custom(_) => return None,
+        };
+
+        Some(cv_type)
+    }
+}
+
+/// This method returns colored (CV_8UC3) image of passed single-channel map (neighbours count
+/// or amplitude map). The map values are scaled from [0, max_value] range to [0, 255] and
+/// replaced by colors of passed colormap. Zero pixels of map stay black, so rendered image
+/// may be used as overlay.
+///
+/// ## Parameters:
+/// * map: (&CvlMat) a single-channel map to render.
+/// * colormap: (&Colormap) a colormap to apply.
+/// * max_value: (f64) a map value which corresponds to the last color of colormap.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to apply colormap.
+pub fn render_map(map: &CvlMat, colormap: &Colormap, max_value: f64) -> ProcessingResult {
+    let scale = match max_value > 0f64 {
+        true => 255f64 / max_value,
+        false => 1f64,
+    };
+
+    let mut map_u8 = Mat::default();
+    if let Err(err) = map.frame().convert_to(&mut map_u8, CV_8U, scale, 0f64) {
+        return Err(ProcessingError::opencv("render_map", map.frame(), err));
+    }
+
+    let mut colored = apply_colormap(&map_u8, colormap)?;
+    let mut zeros_mask = Mat::default();
+    let result = compare(&map_u8, &Scalar::all(0f64), &mut zeros_mask, CMP_EQ)
+        .and_then(|_| colored.set_to(&Scalar::all(0f64), &zeros_mask));
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(colored)),
+        Err(err) => Err(ProcessingError::opencv("render_map", map.frame(), err)),
+    }
+}
+
+/// This method converts vibration image (CV_64FC4, see [`compute_vibration`](crate::compute_vibration))
+/// to CV_8UC3 image which may be displayed or saved.
+///
+/// ## Parameters:
+/// * vibration: (&CvlMat) a vibration image to convert.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to convert image.
+pub fn render_vibration(vibration: &CvlMat) -> ProcessingResult {
+    let mut vibration_u8 = Mat::default();
+    let mut rendered = Mat::default();
+    let result = vibration
+        .frame()
+        .convert_to(&mut vibration_u8, CV_8U, 1f64, 0f64)
+        .and_then(|_| cvt_color(&vibration_u8, &mut rendered, COLOR_BGRA2BGR, 0));
+
+    match result {
+        Ok(_) => Ok(CvlMat::from(rendered)),
+        Err(err) => Err(ProcessingError::opencv(
+            "render_vibration",
+            vibration.frame(),
+            err,
+        )),
+    }
+}
+
+/// This method returns colorbar legend (CV_8UC3) for passed colormap. The colorbar presents
+/// colors of [0, max_value] range from bottom to top, the levels of passed color bounds are
+/// marked by lines with labels.
+///
+/// ## Parameters:
+/// * colormap: (&Colormap) a colormap to render.
+/// * bounds: (&ColorBounds) a color bounds which levels are labelled.
+/// * max_value: (f64) a map value which corresponds to the top of colorbar.
+/// * height: (i32) a height of colorbar image.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to draw colorbar.
+pub fn render_colorbar(
+    colormap: &Colormap,
+    bounds: &ColorBounds,
+    max_value: f64,
+    height: i32,
+) -> ProcessingResult {
+    let gradient = match create_gradient_mat(height) {
+        Ok(mat) => mat,
+        Err(err) => {
+            let empty = Mat::default();
+            return Err(ProcessingError::opencv("render_colorbar", &empty, err));
+        }
+    };
+
+    let colored = apply_colormap(&gradient, colormap)?;
+    match draw_colorbar_labels(&colored, bounds, max_value) {
+        Ok(colorbar) => Ok(CvlMat::from(colorbar)),
+        Err(err) => Err(ProcessingError::opencv("render_colorbar", &colored, err)),
+    }
+}
+
+/// This method returns passed image with attached colorbar to the right side. Both images
+/// must have the same height and type.
+///
+/// ## Parameters:
+/// * image: (&CvlMat) a rendered image.
+/// * colorbar: (&CvlMat) a colorbar (see [`render_colorbar`]).
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if failed while trying to concatenate images.
+pub fn attach_colorbar(image: &CvlMat, colorbar: &CvlMat) -> ProcessingResult {
+    let mut result = Mat::default();
+    match hconcat2(image.frame(), colorbar.frame(), &mut result) {
+        Ok(_) => Ok(CvlMat::from(result)),
+        Err(err) => Err(ProcessingError::opencv(
+            "attach_colorbar",
+            image.frame(),
+            err,
+        )),
+    }
+}
+
+/// This method returns source frame with alpha-blended vibration image on top of it. Only
+/// nonzero pixels of overlay image are blended, other pixels keep the source frame values.
+/// The overlay image may be vibration image (CV_64FC4), rendered map (CV_8UC3) or any
+/// single-channel 8-bit image, the source frame may be BGR or grayscale image of the same size.
+///
+/// ## Parameters:
+/// * source: (&CvlMat) a source video stream frame.
+/// * overlay: (&CvlMat) an image to blend onto source frame.
+/// * opacity: (f64) an opacity of overlay image within [0, 1] range.
+/// * grayscale: (bool) a flag to convert source frame to grayscale before blending.
+///
+/// ## Returns:
+/// Returns `Ok(CvlMat)` of CV_8UC3 type on success, otherwise returns an error.
+///
+/// ## Errors:
+/// Returns [`OpenCv`](ProcessingError::OpenCv) if frames have different sizes or failed while
+/// trying to blend passed images.
+pub fn render_overlay(
+    source: &CvlMat,
+    overlay: &CvlMat,
+    opacity: f64,
+    grayscale: bool,
+) -> ProcessingResult {
+    match blend_overlay(source.frame(), overlay.frame(), opacity, grayscale) {
+        Ok(blended) => Ok(CvlMat::from(blended)),
+        Err(err) => Err(ProcessingError::opencv(
+            "render_overlay",
+            overlay.frame(),
+            err,
+        )),
+    }
+}
+
+fn blend_overlay(
+    source: &Mat,
+    overlay: &Mat,
+    opacity: f64,
+    grayscale: bool,
+) -> opencv::Result<Mat> {
+    let is_same_size = source.rows() == overlay.rows() && source.cols() == overlay.cols();
+    if !is_same_size {
+        let msg = "Source and overlay frames must have the same size.";
+        return Err(opencv::Error::new(opencv::core::StsUnmatchedSizes, msg));
+    }
+
+    let background = convert_background(source, grayscale)?;
+    let foreground = convert_foreground(overlay)?;
+
+    let mut foreground_gray = Mat::default();
+    let mut mask = Mat::default();
+    cvt_color(&foreground, &mut foreground_gray, COLOR_BGR2GRAY, 0)?;
+    compare(&foreground_gray, &Scalar::all(0f64), &mut mask, CMP_GT)?;
+
+    let alpha = opacity.clamp(0f64, 1f64);
+    let mut blended = Mat::default();
+    add_weighted(
+        &background,
+        1f64 - alpha,
+        &foreground,
+        alpha,
+        0f64,
+        &mut blended,
+        -1,
+    )?;
+
+    let mut result = background.clone();
+    blended.copy_to_masked(&mut result, &mask)?;
+    Ok(result)
+}
+
+fn convert_background(source: &Mat, grayscale: bool) -> opencv::Result<Mat> {
+    let mut background = Mat::default();
+    match (source.channels(), grayscale) {
+        (1, _) => cvt_color(source, &mut background, COLOR_GRAY2BGR, 0)?,
+        (_, false) => source.copy_to(&mut background)?,
+        (_, true) => {
+            let mut gray = Mat::default();
+            cvt_color(source, &mut gray, COLOR_BGR2GRAY, 0)?;
+            cvt_color(&gray, &mut background, COLOR_GRAY2BGR, 0)?;
+        }
+    };
+
+    Ok(background)
+}
+
+fn convert_foreground(overlay: &Mat) -> opencv::Result<Mat> {
+    let mut foreground = Mat::default();
+    match overlay.typ() {
+        CV_8UC3 => overlay.copy_to(&mut foreground)?,
+        CV_64FC4 => {
+            let mut overlay_u8 = Mat::default();
+            overlay.convert_to(&mut overlay_u8, CV_8U, 1f64, 0f64)?;
+            cvt_color(&overlay_u8, &mut foreground, COLOR_BGRA2BGR, 0)?;
+        }
+        _ => {
+            let mut overlay_u8 = Mat::default();
+            overlay.convert_to(&mut overlay_u8, CV_8U, 1f64, 0f64)?;
+            cvt_color(&overlay_u8, &mut foreground, COLOR_GRAY2BGR, 0)?;
+        }
+    };
+
+    Ok(foreground)
+}
+
+fn apply_colormap(map_u8: &Mat, colormap: &Colormap) -> Result<Mat, ProcessingError> {
+    let mut colored = Mat::default();
+    let result = match colormap {
+        Colormap::Custom(colors) => {
+            create_lut_mat(colors).and_then(|lut| apply_color_map_user(map_u8, &mut colored, &lut))
+        }
+        _ => {
+            let cv_type = colormap.cv_type().unwrap_or_default();
+            apply_color_map(map_u8, &mut colored, cv_type)
+        }
+    };
+
+    match result {
+        Ok(_) => Ok(colored),
+        Err(err) => Err(ProcessingError::opencv("apply_colormap", map_u8, err)),
+    }
+}
+
+fn create_lut_mat(colors: &[[u8; 3]]) -> opencv::Result<Mat> {
+    if colors.len() != LUT_SIZE {
+        let msg = format!("Custom colormap must contain {} colors", LUT_SIZE);
+        return Err(opencv::Error::new(opencv::core::StsBadArg, msg));
+    }
+
+    let mut lut = Mat::new_rows_cols_with_default(LUT_SIZE as i32, 1, CV_8UC3, Scalar::all(0f64))?;
+    for (index, color) in colors.iter().enumerate() {
+        *lut.at_2d_mut::<Vec3b>(index as i32, 0)? = VecN(*color);
+    }
+
+    Ok(lut)
+}
+
+fn create_gradient_mat(height: i32) -> opencv::Result<Mat> {
+    let height = height.max(2);
+    let mut gradient =
+        Mat::new_rows_cols_with_default(height, COLORBAR_WIDTH, CV_8U, Scalar::all(0f64))?;
+    for row in 0..height {
+        let value = 255 * (height - 1 - row) / (height - 1);
+        for col in 0..COLORBAR_WIDTH {
+            *gradient.at_2d_mut::<u8>(row, col)? = value as u8;
+        }
+    }
+
+    Ok(gradient)
+}
+
+fn draw_colorbar_labels(
+    colored: &Mat,
+    bounds: &ColorBounds,
+    max_value: f64,
+) -> opencv::Result<Mat> {
+    let height = colored.rows();
+    let labels = Mat::new_rows_cols_with_default(height, LABELS_WIDTH, CV_8UC3, Scalar::all(0f64))?;
+
+    let mut colorbar = Mat::default();
+    hconcat2(colored, &labels, &mut colorbar)?;
+
+    let white = Scalar::new(255.0, 255.0, 255.0, 0.0);
+    let total_width = COLORBAR_WIDTH + LABELS_WIDTH;
+    for index in 1..=4 {
+        let level = bounds.get(index) as f64;
+        if max_value <= 0f64 || level > max_value {
+            continue;
+        }
+
+        let row = ((1f64 - level / max_value) * (height - 1) as f64) as i32;
+        let (start, end) = (Point::new(0, row), Point::new(total_width - 1, row));
+        line(&mut colorbar, start, end, white, 1, LINE_8, 0)?;
+
+        let label = format!("{}", bounds.get(index));
+        let origin = Point::new(COLORBAR_WIDTH + 4, (row - 2).max(10));
+        put_text(
+            &mut colorbar,
+            &label,
+            origin,
+            FONT_HERSHEY_SIMPLEX,
+            0.4,
+            white,
+            1,
+            LINE_8,
+            false,
+        )?;
+    }
+
+    Ok(colorbar)
+}
@@ -53,7 +53,7 @@ mod benchmark {
         b.iter(|| {
             let cvlmat = CvlMat::new(mat.clone());
             let gray = gen_grayscale_frame(&cvlmat).unwrap();
-            let _ = gen_canny_frame_by_sigma(&gray, 3, 0.05, true).unwrap();
+            let _ = gen_canny_frame_by_mean_legacy(&gray, 3, 0.05, true).unwrap();
         });
     }
 
@@ -85,7 +85,7 @@ mod benchmark {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -100,7 +100,7 @@ mod benchmark {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -120,7 +120,7 @@ mod benchmark {
                 .into_iter()
                 .map(CvlMat::new)
                 .map(|m| gen_grayscale_frame(&m).unwrap())
-                .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+                .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
                 .map(Rc::new)
                 .collect::<Vec<Rc<CvlMat>>>();
 
@@ -135,7 +135,7 @@ mod benchmark {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
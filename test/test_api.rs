@@ -1,15 +1,36 @@
 extern crate cvlcore;
 
+use cvlcore::api::profile::CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
 #[cfg(test)]
 mod main_test {
-    use cvlcore::api::chain::ChainProcessing;
+    use cvlcore::api::chain::{ChainProcessing, ProcessingSettings};
+    use cvlcore::api::events::{ChainEvent, ChainObserver};
+    use cvlcore::api::face::{FaceDetector, FaceModel, FaceTracker};
+    use cvlcore::api::profile::LatencyHistogram;
+    use cvlcore::api::snapshot::ChainSnapshot;
+    use cvlcore::api::stages::{BlurStage, ChainStage, Morphology};
+    use cvlcore::api::state::{ChainOutput, ChainState};
+    use cvlcore::core::bounds::ColorBounds;
+    use cvlcore::core::denoise::Denoise;
+    use cvlcore::core::edge::EdgeDetector;
     use cvlcore::core::mat::CvlMat;
+    use cvlcore::core::motion::Stabilization;
+    use cvlcore::core::roi::RegionOfInterest;
+    use cvlcore::core::scale::Resize;
     use cvlcore::core::statistic::*;
+    use cvlcore::core::zones::{Zone, ZoneLayout};
+    use cvlcore::errors::{ProcessingError, SettingsError, SnapshotError};
     use cvlcore::*;
-    use opencv::core::{Mat, MatTraitConst};
+    use opencv::core::{Mat, MatExprTraitConst, MatTraitConst, Rect, CV_8UC3};
     use opencv::imgcodecs::imread;
+    use std::cell::{Cell, RefCell};
     use std::path::Path;
     use std::rc::Rc;
+    use std::time::Duration;
 
     #[test]
     fn test_chain_processing() {
@@ -21,7 +42,7 @@ mod main_test {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -41,12 +62,671 @@ mod main_test {
         assert_eq!(result.frame().dims(), 2);
     }
 
+    #[test]
+    fn test_chain_edge_detectors() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+
+        let detectors = [
+            EdgeDetector::Canny {
+                low: 100.0,
+                high: 255.0,
+            },
+            EdgeDetector::CannySigma,
+            EdgeDetector::Sobel { ksize: 3 },
+            EdgeDetector::Laplacian { ksize: 3 },
+            EdgeDetector::Scharr,
+        ];
+
+        for detector in detectors {
+            let proc_settings = ProcessingSettings::builder()
+                .edge_detector(detector, 64.0)
+                .build()
+                .unwrap();
+            let mut own_chain = ChainProcessing::new(proc_settings).unwrap();
+            let result = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .edges()
+                .get_result()
+                .unwrap();
+
+            assert_eq!(result.frame().channels(), 1);
+            assert_eq!(result.rows(), mat.rows());
+        }
+    }
+
+    #[test]
+    fn test_chain_denoise() {
+        let frames = load_resource_frames();
+        let rows = frames.first().unwrap().rows();
+
+        let denoise = Denoise::Gaussian {
+            ksize: 5,
+            sigma: 1.5,
+        };
+        let proc_settings = ProcessingSettings::builder()
+            .denoise(denoise, 3)
+            .build()
+            .unwrap();
+        let mut own_chain = ChainProcessing::new(proc_settings).unwrap();
+        for mat in frames {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat))
+                .grayscale()
+                .denoise()
+                .canny();
+        }
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.frame().channels(), 1);
+        assert_eq!(result.rows(), rows);
+    }
+
+    #[test]
+    fn test_chain_resize() {
+        let frames = load_resource_frames();
+        let cols = frames.first().unwrap().cols();
+
+        let proc_settings = ProcessingSettings::builder()
+            .resize(Resize::Scale(0.5), cols)
+            .build()
+            .unwrap();
+        let mut own_chain = ChainProcessing::new(proc_settings).unwrap();
+        for mat in frames {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat))
+                .resize()
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.columns(), cols / 2);
+        assert!(own_chain.get_dispersion().is_some());
+    }
+
+    #[test]
+    fn test_chain_custom_stages() {
+        let frames = load_resource_frames();
+        let frames_count = frames.len();
+
+        let mut blur = BlurStage::new(Denoise::Median { ksize: 3 });
+        let mut calls = 0;
+        let mut counter = |frame: &CvlMat| {
+            calls += 1;
+            Ok(frame.to_owned())
+        };
+
+        let mut own_chain = ChainProcessing::default();
+        own_chain.set_profiling(true);
+        for mat in frames {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat))
+                .grayscale()
+                .stage(&mut blur)
+                .canny()
+                .morphology(Morphology::Close, 3, 1)
+                .threshold(127.0, 255.0)
+                .stage(&mut counter)
+                .map("reblur", |frame| blur.process(frame))
+                .append_frame();
+        }
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.frame().channels(), 1);
+        assert_eq!(calls, frames_count);
+
+        let report = own_chain.profile_report().unwrap();
+        for stage in ["median_blur", "morphology", "threshold", "stage", "reblur"] {
+            let calls = report.stage(stage).unwrap().latency.count();
+            assert_eq!(calls, frames_count as u64);
+        }
+
+        let failed = own_chain
+            .run_chain(result)
+            .map("failing", |_| Err(ProcessingError::ComputeStatistic))
+            .morphology(Morphology::Open, 3, 1)
+            .get_result();
+        assert!(matches!(
+            failed,
+            Err(ProcessingError::Chain {
+                stage: "failing",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_chain_errors() {
+        let frames = load_resource_frames();
+        let mut own_chain = ChainProcessing::default();
+        let result = own_chain
+            .run_chain(CvlMat::new(frames[0].clone()))
+            .grayscale()
+            .canny()
+            .append_frame()
+            .reduce_abs()
+            .get_result();
+        assert!(matches!(
+            result,
+            Err(ProcessingError::NotEnoughFrames {
+                required: 5,
+                available: 1
+            })
+        ));
+        assert!(!result.unwrap_err().is_failure());
+
+        let err = own_chain
+            .run_chain(CvlMat::new(frames[1].clone()))
+            .grayscale()
+            .morphology(Morphology::Close, -1, 1)
+            .threshold(127.0, 255.0)
+            .get_result()
+            .unwrap_err();
+        match &err {
+            ProcessingError::Chain {
+                stage,
+                frame_index,
+                input,
+                ..
+            } => {
+                assert_eq!(*stage, "morphology");
+                assert_eq!(*frame_index, 2);
+                assert_eq!((input.rows, input.cols, input.channels), (360, 640, 1));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err.is_failure());
+        assert!(std::error::Error::source(&err).is_some());
+        assert!(matches!(err.root_cause(), ProcessingError::OpenCv { .. }));
+    }
+
+    #[test]
+    fn test_chain_state() {
+        let frames = load_resource_frames();
+        let mut own_chain = ChainProcessing::default();
+        assert_eq!(own_chain.state(), ChainState::Reset);
+
+        for (index, mat) in frames.iter().take(5).enumerate() {
+            let output = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic()
+                .output();
+
+            match index {
+                4 => {
+                    assert!(matches!(output, ChainOutput::Ready(_)));
+                    assert!(own_chain.state().is_ready());
+                }
+                _ => {
+                    assert!(matches!(output, ChainOutput::WarmingUp { required: 5, .. }));
+                    let state = own_chain.state();
+                    assert_eq!(
+                        state,
+                        ChainState::WarmingUp {
+                            collected: index + 1,
+                            required: 5
+                        }
+                    );
+                    assert_eq!(state.progress(), (index + 1) as f32 / 5.0);
+                }
+            }
+        }
+
+        for failures in 1..=2 {
+            let output = own_chain
+                .run_chain(CvlMat::new(frames[5].clone()))
+                .grayscale()
+                .morphology(Morphology::Close, -1, 1)
+                .output();
+            assert!(matches!(output, ChainOutput::Failed(_)));
+            assert_eq!(own_chain.state(), ChainState::Degraded { failures });
+        }
+
+        own_chain.reset();
+        assert_eq!(own_chain.state(), ChainState::Reset);
+        assert!(own_chain.get_dispersion().is_none());
+
+        let _ = own_chain
+            .run_chain(CvlMat::new(frames[6].clone()))
+            .grayscale()
+            .canny()
+            .append_frame()
+            .reduce_abs();
+        assert_eq!(
+            own_chain.state(),
+            ChainState::WarmingUp {
+                collected: 1,
+                required: 5
+            }
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        stages: Vec<String>,
+        statistics: usize,
+        dispersions: usize,
+        events: Vec<ChainEvent>,
+    }
+
+    impl ChainObserver for RecordingObserver {
+        fn on_stage(&mut self, stage: &str, output: &CvlMat, _elapsed: Duration) {
+            assert!(stage == "append_frame" || !output.empty());
+            self.stages.push(stage.to_string());
+        }
+
+        fn on_statistic(&mut self, _statistic: &Statistic, dispersion: Option<&Dispersion>) {
+            self.statistics += 1;
+            self.dispersions += dispersion.map_or(0, |_| 1);
+        }
+
+        fn on_event(&mut self, event: &ChainEvent) {
+            self.events.push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_chain_observers() {
+        let frames = load_resource_frames();
+        let recorder = Rc::new(RefCell::new(RecordingObserver::default()));
+        let other = Rc::new(RefCell::new(RecordingObserver::default()));
+
+        let mut own_chain = ChainProcessing::default();
+        own_chain.add_observer(Box::new(recorder.clone()));
+        let other_id = own_chain.add_observer(Box::new(other.clone()));
+
+        for mat in frames.iter().take(7) {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let recorded = recorder.borrow();
+        assert_eq!(
+            &recorded.stages[0..3],
+            &["grayscale", "canny", "append_frame"]
+        );
+        assert_eq!(
+            recorded
+                .stages
+                .iter()
+                .filter(|stage| *stage == "vibrating")
+                .count(),
+            3
+        );
+        assert_eq!(recorded.statistics, 3);
+        assert_eq!(recorded.dispersions, 1);
+        assert_eq!(other.borrow().stages, recorded.stages);
+
+        drop(recorded);
+        assert!(own_chain.remove_observer(other_id).is_some());
+        assert!(own_chain.remove_observer(other_id).is_none());
+
+        own_chain
+            .update_settings(ProcessingSettings::default())
+            .unwrap();
+        let _ = own_chain
+            .run_chain(CvlMat::new(frames[7].clone()))
+            .grayscale();
+        assert_eq!(recorder.borrow().events.len(), 1);
+        assert!(other.borrow().events.is_empty());
+    }
+
+    #[test]
+    fn test_chain_profiling() {
+        let frames = load_resource_frames();
+        let mut own_chain = ChainProcessing::default();
+        assert!(own_chain.profile_report().is_none());
+
+        own_chain.set_profiling(true);
+        for mat in frames.iter().take(6) {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating();
+        }
+
+        let report = own_chain.profile_report().unwrap();
+        assert_eq!(report.frames, 6);
+        assert_eq!(report.stages.len(), 5);
+        assert_eq!(report.stage("canny").unwrap().latency.count(), 6);
+        assert_eq!(report.stage("vibrating").unwrap().latency.count(), 2);
+        assert!(report.stage("append_frame").unwrap().allocations.count > 0);
+        assert!(report.fps() > 0.0);
+        assert!(report.processing_fps() >= report.fps());
+
+        let table = report.to_string();
+        assert!(table.starts_with("stage"));
+        assert!(table.contains("reduce_abs"));
+        assert!(table.contains("fps"));
+
+        own_chain.reset_profiling();
+        assert_eq!(own_chain.profile_report().unwrap().frames, 0);
+        own_chain.set_profiling(false);
+        assert!(own_chain.profile_report().is_none());
+
+        let mut histogram = LatencyHistogram::default();
+        for millis in [1, 1, 1, 10] {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.min(), Duration::from_millis(1));
+        assert_eq!(histogram.percentile(50.0), Duration::from_micros(1024));
+        assert_eq!(histogram.percentile(100.0), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_chain_branches() {
+        let frames = load_resource_frames();
+        let mut own_chain = ChainProcessing::default();
+        let mut plain_chain = ChainProcessing::default();
+        let strict_bounds = ColorBounds::new(12, 13, 14, 15);
+
+        for mat in frames.iter().take(8) {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .tap("raw")
+                .grayscale()
+                .tap("gray")
+                .canny()
+                .tap("edges")
+                .append_frame()
+                .reduce_abs()
+                .tap("diff")
+                .branch("vibro_strict", |chain| {
+                    chain.with_bounds(strict_bounds).vibrating().statistic();
+                })
+                .vibrating()
+                .tap("vibro")
+                .statistic()
+                .overlay()
+                .tap("overlay");
+
+            let _ = plain_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let taps = own_chain.get_taps();
+        assert_eq!(taps.len(), 7);
+        assert!(taps.values().all(|result| result.is_ok()));
+        assert_eq!(own_chain.get_tap("raw").unwrap().channels(), 3);
+        assert_eq!(own_chain.get_tap("gray").unwrap().channels(), 1);
+        assert_eq!(own_chain.get_tap("diff").unwrap().channels(), 1);
+        assert_eq!(own_chain.get_tap("overlay").unwrap().channels(), 3);
+        assert!(own_chain.get_tap("missing").is_none());
+
+        let total = |name: &str| {
+            let stat = own_chain.get_tap(name).unwrap().statistic().unwrap();
+            stat.ch1 as u32 + stat.ch2 as u32 + stat.ch3 as u32 + stat.ch4 as u32
+        };
+        assert!(total("vibro_strict") <= total("vibro"));
+
+        let dispersion = own_chain.get_dispersion().unwrap();
+        let plain_dispersion = plain_chain.get_dispersion().unwrap();
+        assert_eq!(dispersion.ch1, plain_dispersion.ch1);
+        assert_eq!(dispersion.ch4, plain_dispersion.ch4);
+
+        let failed = own_chain
+            .run_chain(CvlMat::new(frames[8].clone()))
+            .branch("invalid", |chain| {
+                chain.with_bounds(ColorBounds::new(3, 2, 1, 0));
+            })
+            .grayscale()
+            .tap("gray");
+        assert!(failed.get_taps()["invalid"].is_err());
+        assert!(failed.get_tap("gray").is_some());
+        assert!(failed.get_tap("raw").is_none());
+    }
+
+    #[test]
+    fn test_chain_branch_history() {
+        let frames = load_resource_frames();
+        let settings = || {
+            ProcessingSettings::builder()
+                .stabilization(Stabilization::PhaseCorrelation)
+                .denoise(Denoise::Median { ksize: 3 }, 3)
+                .build()
+                .unwrap()
+        };
+
+        let mut own_chain = ChainProcessing::new(settings()).unwrap();
+        let mut plain_chain = ChainProcessing::new(settings()).unwrap();
+        for mat in frames.iter().take(6) {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .branch("stabilized", |chain| {
+                    chain.denoise().stabilize();
+                })
+                .denoise()
+                .tap("denoised")
+                .stabilize();
+
+            let _ = plain_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .denoise()
+                .tap("denoised")
+                .stabilize();
+
+            let own_denoised = own_chain.get_tap("denoised").unwrap().frame();
+            let plain_denoised = plain_chain.get_tap("denoised").unwrap().frame();
+            assert_eq!(
+                own_denoised.data_bytes().unwrap(),
+                plain_denoised.data_bytes().unwrap()
+            );
+        }
+
+        assert!(own_chain.get_tap("stabilized").is_some());
+        let (own_motions, plain_motions) = (own_chain.get_motions(), plain_chain.get_motions());
+        assert_eq!(own_motions.length(), plain_motions.length());
+        for (own, plain) in own_motions.iter().zip(plain_motions.iter()) {
+            assert_eq!((own.dx, own.dy), (plain.dx, plain.dy));
+        }
+    }
+
+    #[test]
+    fn test_chain_snapshot() {
+        let frames = load_resource_frames();
+        let mut own_chain = ChainProcessing::default();
+        for mat in frames.iter().take(8) {
+            let _ = own_chain
+                .run_chain(CvlMat::new(mat.clone()))
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let dispersion = own_chain.get_dispersion().cloned().unwrap();
+        let path = std::env::temp_dir().join("cvlcore_test_chain_snapshot.json");
+        own_chain.save_snapshot(&path).unwrap();
+        let mut restored = ChainProcessing::load_snapshot(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(restored.frame_index(), own_chain.frame_index());
+        let restored_dispersion = restored.get_dispersion().unwrap();
+        assert_eq!(restored_dispersion.ch1, dispersion.ch1);
+        assert_eq!(restored_dispersion.ch4, dispersion.ch4);
+
+        let output = restored
+            .run_chain(CvlMat::new(frames[8].clone()))
+            .grayscale()
+            .canny()
+            .append_frame()
+            .reduce_abs()
+            .output();
+        assert!(matches!(output, ChainOutput::Ready(_)));
+        assert_eq!(restored.frame_index(), own_chain.frame_index() + 1);
+
+        let newer = r#"{"version": 99}"#;
+        assert!(matches!(
+            ChainSnapshot::from_json_str(newer),
+            Err(SnapshotError::UnsupportedVersion { found: 99, .. })
+        ));
+        assert!(matches!(
+            ChainSnapshot::from_json_str("not a snapshot"),
+            Err(SnapshotError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_settings_validation() {
+        let settings = ProcessingSettings::builder()
+            .frames_count(10)
+            .canny(5, 0.33, true)
+            .denoise(Denoise::Median { ksize: 3 }, 3)
+            .build()
+            .unwrap();
+        assert!(ChainProcessing::new(settings).is_ok());
+
+        let result = ProcessingSettings::builder().frames_count(0).build();
+        assert_eq!(
+            result.err(),
+            Some(SettingsError::NotPositive("frames_count"))
+        );
+
+        let result = ProcessingSettings::builder().canny(4, 0.05, true).build();
+        assert_eq!(result.err(), Some(SettingsError::InvalidCannyKsize));
+
+        let result = ProcessingSettings::builder().overlay(1.5, true).build();
+        assert_eq!(result.err().map(|err| err.key()), Some("overlay_opacity"));
+
+        let result = ProcessingSettings::builder()
+            .denoise(
+                Denoise::Gaussian {
+                    ksize: 4,
+                    sigma: 1.0,
+                },
+                0,
+            )
+            .build();
+        assert_eq!(
+            result.err(),
+            Some(SettingsError::InvalidKernelSize("denoise"))
+        );
+
+        let settings = ProcessingSettings {
+            window_size: 0,
+            ..ProcessingSettings::default()
+        };
+        assert!(ChainProcessing::new(settings).is_err());
+
+        assert!(ColorBounds::try_new(8, 9, 10, 11).is_ok());
+        assert_eq!(
+            ColorBounds::try_new(8, 11, 10, 9).err(),
+            Some(SettingsError::Unsorted("bounds"))
+        );
+
+        let mut own_chain = ChainProcessing::default();
+        assert!(own_chain.set_bounds(ColorBounds::new(0, 1, 2, 3)).is_err());
+    }
+
+    #[test]
+    fn test_chain_update_settings() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+
+        let mut own_chain = ChainProcessing::default();
+        for cvlmat in all_frames.iter().cloned() {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+        assert!(own_chain.take_events().is_empty());
+
+        let invalid = ProcessingSettings {
+            frames_count: 0,
+            ..ProcessingSettings::default()
+        };
+        assert!(own_chain.update_settings(invalid).is_err());
+
+        let settings = ProcessingSettings {
+            frames_count: 3,
+            overlay_opacity: 0.3,
+            ..ProcessingSettings::default()
+        };
+        own_chain.update_settings(settings).unwrap();
+        assert_eq!(own_chain.settings().frames_count, 5);
+
+        let frame_index = own_chain.frame_index() + 1;
+        let _ = own_chain
+            .run_chain(all_frames[0].clone())
+            .grayscale()
+            .canny()
+            .append_frame()
+            .reduce_abs();
+        assert!(own_chain.get_result().is_ok());
+        assert_eq!(
+            own_chain.take_events(),
+            vec![ChainEvent::SettingsChanged {
+                frame_index,
+                is_segmented: false,
+            }]
+        );
+
+        let settings = ProcessingSettings {
+            neighbours: 6,
+            ..ProcessingSettings::default()
+        };
+        own_chain.update_settings(settings).unwrap();
+        let _ = own_chain
+            .run_chain(all_frames[1].clone())
+            .grayscale()
+            .canny()
+            .append_frame()
+            .reduce_abs();
+        assert!(own_chain.get_result().is_err());
+        assert!(own_chain.get_dispersion().is_none());
+
+        let events = own_chain.take_events();
+        assert!(matches!(
+            events.as_slice(),
+            [ChainEvent::SettingsChanged {
+                is_segmented: true,
+                ..
+            }]
+        ));
+    }
+
     #[test]
     fn test_chain_statistic() {
         let frames = load_resource_frames();
         let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
 
-        let mut dispertion = Dispersion::default();
+        let mut frame_stats = Vec::new();
         let mut own_chain = ChainProcessing::default();
         for cvlmat in all_frames {
             let precessing_result = own_chain
@@ -58,16 +738,263 @@ mod main_test {
                 .vibrating()
                 .statistic();
 
-            match precessing_result.get_dispersion() {
-                None => continue,
-                Some(result) => dispertion = result.clone(),
+            if let Ok(frame) = precessing_result.get_result() {
+                frame_stats.push(frame.statistic().cloned().unwrap());
             }
         }
 
-        assert_eq!(dispertion.ch1, 177.78374);
-        assert_eq!(dispertion.ch2, 78.44896);
-        assert_eq!(dispertion.ch3, 198.52461);
-        assert_eq!(dispertion.ch4, 141.05609);
+        // Each frame adds single statistic to history, so dispersion is computed over
+        // statistics of last `frames_count` processed frames.
+        let settings = own_chain.settings();
+        assert!(frame_stats.len() >= settings.frames_count);
+        let window = &frame_stats[frame_stats.len() - settings.frames_count..];
+        let expected = compute_statistic(window.iter().collect(), settings.normalization);
+
+        let dispertion = own_chain.get_dispersion().unwrap();
+        assert_eq!(dispertion.ch1, expected.ch1);
+        assert_eq!(dispertion.ch2, expected.ch2);
+        assert_eq!(dispertion.ch3, expected.ch3);
+        assert_eq!(dispertion.ch4, expected.ch4);
+    }
+
+    #[test]
+    fn test_chain_amplitude() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+
+        let mut own_chain = ChainProcessing::default();
+        for cvlmat in all_frames {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .append_frame()
+                .amplitude()
+                .colorize();
+        }
+
+        let amplitude = own_chain.get_amplitude().unwrap();
+        assert_eq!(amplitude.frame().channels(), 1);
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.frame().channels(), 3);
+    }
+
+    #[test]
+    fn test_chain_overlay() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+
+        let mut own_chain = ChainProcessing::default();
+        for cvlmat in all_frames {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .overlay();
+        }
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.frame().channels(), 3);
+    }
+
+    #[test]
+    fn test_chain_roi_statistic() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+
+        let rect = Rect::new(50, 50, 200, 200);
+        let proc_settings = ProcessingSettings::builder()
+            .roi(RegionOfInterest::Rectangles(vec![rect]))
+            .build()
+            .unwrap();
+        let mut own_chain = ChainProcessing::new(proc_settings).unwrap();
+        for cvlmat in all_frames {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let result = own_chain.get_result().unwrap();
+        assert_eq!(result.statistic().unwrap().area, 200 * 200);
+        assert!(own_chain.get_dispersion().is_some());
+    }
+
+    #[test]
+    fn test_chain_zones_statistic() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+
+        let head = Zone::new(
+            "head",
+            RegionOfInterest::Rectangles(vec![Rect::new(0, 0, 200, 200)]),
+            ColorBounds::new(6, 8, 10, 12),
+        );
+
+        let mut own_chain = ChainProcessing::default();
+        own_chain.set_zones(ZoneLayout::Zones(vec![head]));
+        for cvlmat in all_frames.iter().cloned() {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let zones = own_chain.get_zones_statistic();
+        assert_eq!(zones.len(), 1);
+        assert!(zones.get("head").unwrap().dispersion().is_some());
+
+        let grid = ZoneLayout::Grid {
+            rows: 2,
+            cols: 3,
+            bounds: ColorBounds::default(),
+        };
+
+        own_chain.set_zones(grid);
+        for cvlmat in all_frames {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .canny()
+                .append_frame()
+                .reduce_abs()
+                .vibrating()
+                .statistic();
+        }
+
+        let zones = own_chain.get_zones_statistic();
+        assert_eq!(zones.len(), 6);
+        assert!(zones.contains_key("cell_1_2"));
+    }
+
+    #[test]
+    fn test_chain_stabilization() {
+        let frames = load_resource_frames();
+        let all_frames = frames.into_iter().map(CvlMat::new).collect::<Vec<CvlMat>>();
+        let frames_count = all_frames.len();
+
+        let proc_settings = ProcessingSettings::builder()
+            .stabilization(Stabilization::PhaseCorrelation)
+            .frames_count(frames_count)
+            .build()
+            .unwrap();
+        let mut own_chain = ChainProcessing::new(proc_settings).unwrap();
+        for cvlmat in all_frames {
+            let _ = own_chain
+                .run_chain(cvlmat)
+                .grayscale()
+                .stabilize()
+                .canny()
+                .append_frame();
+        }
+
+        let motions = own_chain.get_motions();
+        assert_eq!(motions.length(), frames_count);
+        assert_eq!(motions.first().unwrap().magnitude(), 0.0);
+    }
+
+    #[test]
+    fn test_face_tracker_missing_model() {
+        let model = FaceModel::Cascade("test/resources/missing_cascade.xml".to_string());
+        assert!(FaceTracker::new(model).is_err());
+
+        let model = FaceModel::Dnn {
+            model: "test/resources/missing_model.caffemodel".to_string(),
+            config: "test/resources/missing_model.prototxt".to_string(),
+            confidence: 0.5,
+        };
+        assert!(FaceTracker::new(model).is_err());
+    }
+
+    #[test]
+    fn test_face_tracker_tracking() {
+        let frames = load_resource_frames();
+        let frame = CvlMat::new(frames[0].clone());
+        let black = CvlMat::new(Mat::zeros(360, 640, CV_8UC3).unwrap().to_mat().unwrap());
+        let rect = Rect::new(200, 100, 120, 120);
+
+        let detector = ScriptedDetector::default();
+        let (face, calls) = (detector.face.clone(), detector.calls.clone());
+        face.set(Some(rect));
+
+        let model = FaceModel::Detector(Box::new(detector));
+        let mut tracker = FaceTracker::new(model).unwrap();
+        tracker.set_detect_every(10);
+        tracker.set_expand_ratio(1.0);
+        assert_eq!(tracker.update(&frame).unwrap(), Some(rect));
+
+        face.set(None);
+        for _ in 0..3 {
+            assert_eq!(tracker.update(&frame).unwrap(), Some(rect));
+        }
+        assert_eq!(calls.get(), 1);
+
+        assert_eq!(tracker.update(&black).unwrap(), None);
+        assert_eq!(tracker.face(), None);
+        assert_eq!(calls.get(), 2);
+
+        face.set(Some(rect));
+        assert_eq!(tracker.update(&frame).unwrap(), Some(rect));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn test_chain_no_face() {
+        let frames = load_resource_frames();
+        let detector = ScriptedDetector::default();
+        let face = detector.face.clone();
+
+        let mut own_chain = ChainProcessing::default();
+        let tracker = FaceTracker::new(FaceModel::Detector(Box::new(detector))).unwrap();
+        own_chain.set_face_tracker(tracker);
+
+        for frame in frames.iter().take(3) {
+            let result = own_chain
+                .run_chain(CvlMat::new(frame.clone()))
+                .track_face()
+                .grayscale()
+                .get_result();
+            assert!(matches!(result, Err(ProcessingError::NoFaceDetected)));
+            assert!(matches!(own_chain.output(), ChainOutput::NoFace));
+            assert_eq!(own_chain.state(), ChainState::NoFace);
+            assert_eq!(own_chain.get_face(), None);
+        }
+
+        face.set(Some(Rect::new(200, 100, 120, 120)));
+        let result = own_chain
+            .run_chain(CvlMat::new(frames[3].clone()))
+            .track_face()
+            .grayscale()
+            .get_result();
+        assert!(result.is_ok());
+        assert!(matches!(own_chain.output(), ChainOutput::Ready(_)));
+        assert_eq!(own_chain.state(), ChainState::Ready);
+        assert!(own_chain.get_face().is_some());
+    }
+
+    /// A face detector which returns face set by test.
+    #[derive(Default)]
+    struct ScriptedDetector {
+        face: Rc<Cell<Option<Rect>>>,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl FaceDetector for ScriptedDetector {
+        fn detect(&mut self, _: &Mat, _: &Mat) -> opencv::Result<Vec<Rect>> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(self.face.get().into_iter().collect())
+        }
     }
 
     fn load_resource_frames() -> Vec<Mat> {
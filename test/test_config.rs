@@ -0,0 +1,132 @@
+extern crate cvlcore;
+
+#[cfg(test)]
+mod config_test {
+    use cvlcore::api::config::*;
+    use cvlcore::core::mat::CvlMat;
+    use cvlcore::errors::ConfigError;
+    use opencv::core::{Mat, MatTraitConst};
+    use opencv::imgcodecs::imread;
+    use std::path::Path;
+
+    #[test]
+    fn test_pipeline_from_toml() {
+        let path = Path::new("test/resources/configs/pipeline.toml");
+        let config = PipelineConfig::from_file(path).unwrap();
+        assert_eq!(config.stages.len(), 8);
+
+        let mut pipeline = config.build().unwrap();
+        for mat in load_resource_frames() {
+            let _ = pipeline.run(CvlMat::new(mat));
+        }
+
+        let result = pipeline.run(CvlMat::new(load_resource_frames().remove(0)));
+        assert_eq!(result.unwrap().frame().channels(), 4);
+        assert!(pipeline.chain().get_dispersion().is_some());
+    }
+
+    #[test]
+    fn test_pipeline_from_json() {
+        let path = Path::new("test/resources/configs/pipeline.json");
+        let mut pipeline = PipelineConfig::from_file(path).unwrap().build().unwrap();
+        assert_eq!(pipeline.stages().len(), 8);
+
+        for mat in load_resource_frames() {
+            let _ = pipeline.run(CvlMat::new(mat));
+        }
+
+        assert!(pipeline.chain().get_dispersion().is_some());
+    }
+
+    #[test]
+    fn test_pipeline_invalid_key() {
+        let text = r#"
+            [settings]
+            frames_count = "five"
+
+            [[stages]]
+            stage = "grayscale"
+        "#;
+
+        match PipelineConfig::from_toml_str(text) {
+            Err(ConfigError::InvalidValue { path, .. }) => {
+                assert_eq!(path, "settings.frames_count")
+            }
+            _ => panic!("expected invalid value error"),
+        }
+
+        let text = r#"{ "stages": [{ "stage": "grayscale" }, { "stage": "morphology", "operation": "open", "ksize": 0 }] }"#;
+        match PipelineConfig::from_json_str(text) {
+            Err(ConfigError::InvalidValue { path, .. }) => assert_eq!(path, "stages[1].ksize"),
+            _ => panic!("expected invalid value error"),
+        }
+
+        let text = r#"{ "stages": [{ "stage": "unknown" }] }"#;
+        match PipelineConfig::from_json_str(text) {
+            Err(ConfigError::InvalidValue { path, .. }) => assert_eq!(path, "stages[0]"),
+            _ => panic!("expected invalid value error"),
+        }
+
+        let text = r#"{ "bounds": [8, 12, 10, 11], "stages": [{ "stage": "grayscale" }] }"#;
+        match PipelineConfig::from_json_str(text) {
+            Err(ConfigError::InvalidValue { path, .. }) => assert_eq!(path, "bounds"),
+            _ => panic!("expected invalid value error"),
+        }
+
+        let text = r#"{ "settings": { "canny_ksize": 4 }, "stages": [{ "stage": "canny" }] }"#;
+        match PipelineConfig::from_json_str(text) {
+            Err(ConfigError::InvalidValue { path, .. }) => assert_eq!(path, "settings.canny_ksize"),
+            _ => panic!("expected invalid value error"),
+        }
+
+        let path = Path::new("test/resources/configs/pipeline.yaml");
+        assert!(PipelineConfig::from_file(path).is_err());
+    }
+
+    #[test]
+    fn test_config_watcher() {
+        let source = Path::new("test/resources/configs/pipeline.toml");
+        let path = std::env::temp_dir().join("cvlcore_watched_pipeline.toml");
+        std::fs::copy(source, &path).unwrap();
+
+        let mut watcher = ConfigWatcher::new(&path);
+        assert!(watcher.poll().unwrap().is_none());
+
+        let mut pipeline = PipelineConfig::from_file(&path).unwrap().build().unwrap();
+        let text = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("frames_count = 5", "frames_count = 3");
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(&path, text).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        let modified = std::time::SystemTime::now() + std::time::Duration::from_secs(1);
+        file.set_modified(modified).unwrap();
+
+        let config = watcher.poll().unwrap().unwrap();
+        assert_eq!(config.settings.frames_count, 3);
+        pipeline.update(config).unwrap();
+        assert!(watcher.poll().unwrap().is_none());
+
+        let _ = pipeline.run(CvlMat::new(load_resource_frames().remove(0)));
+        assert_eq!(pipeline.chain().settings().frames_count, 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn load_resource_frames() -> Vec<Mat> {
+        let flags = 3;
+        let mut paths = Path::new("test/resources/")
+            .read_dir()
+            .unwrap()
+            .map(Result::unwrap)
+            .filter(|f| f.file_name().to_str().unwrap().contains("test_file_"))
+            .map(|f| f.path().to_str().unwrap().to_string())
+            .collect::<Vec<String>>();
+
+        paths.sort();
+        paths
+            .iter()
+            .map(|f| imread(f.as_str(), flags).unwrap())
+            .collect()
+    }
+}
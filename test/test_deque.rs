@@ -23,6 +23,48 @@ mod test {
         assert_eq!(cvl_deque.length(), 5);
     }
 
+    #[test]
+    fn test_cvlmat_deque_ring() {
+        let mut cvl_deque: CvlMatDeque<i32> = CvlMatDeque::new(4);
+        assert!(cvl_deque.is_empty());
+        assert_eq!(cvl_deque.push(1), None);
+        cvl_deque.extend(2..=6);
+        assert!(cvl_deque.is_full());
+        assert_eq!(cvl_deque.push(7), Some(3));
+        assert_eq!(
+            cvl_deque.iter().copied().collect::<Vec<i32>>(),
+            [4, 5, 6, 7]
+        );
+        assert_eq!(cvl_deque.iter().next_back(), Some(&7));
+        assert_eq!((cvl_deque[0], cvl_deque[3]), (4, 7));
+        assert_eq!(cvl_deque.get(4), None);
+
+        let (oldest, newest) = cvl_deque.as_slices();
+        assert_eq!([oldest, newest].concat(), [4, 5, 6, 7]);
+
+        let windows = cvl_deque
+            .windows(3)
+            .map(|window| window.sum::<i32>())
+            .collect::<Vec<i32>>();
+        assert_eq!(windows, [15, 18]);
+        assert_eq!(cvl_deque.windows(5).count(), 0);
+        assert_eq!(cvl_deque.windows(0).count(), 0);
+
+        assert_eq!(cvl_deque.take_first(), Some(4));
+        assert_eq!(cvl_deque.take_last(), Some(7));
+        assert_eq!(cvl_deque.push(8), None);
+        assert_eq!(cvl_deque.make_contiguous(), [5, 6, 8]);
+
+        cvl_deque.set_max_size(2);
+        assert_eq!(cvl_deque.iter().copied().collect::<Vec<i32>>(), [6, 8]);
+        cvl_deque.set_max_size(3);
+        assert_eq!(cvl_deque.max_size(), 3);
+        assert_eq!(cvl_deque.push(9), None);
+        assert_eq!(cvl_deque.push(10), Some(6));
+        assert_eq!(cvl_deque.iter().copied().collect::<Vec<i32>>(), [8, 9, 10]);
+        assert_eq!(cvl_deque.inner, [8, 9, 10]);
+    }
+
     #[test]
     fn test_cvlmat_deque_mat() {
         let cvl_deque = CvlMatDeque::from(load_resource_frames());
@@ -3,10 +3,18 @@ extern crate cvlcore;
 #[cfg(test)]
 mod main_test {
     use cvlcore::core::bounds::*;
+    use cvlcore::core::denoise::*;
+    use cvlcore::core::flicker::*;
+    use cvlcore::core::illumination::*;
     use cvlcore::core::mat::*;
+    use cvlcore::core::motion::*;
+    use cvlcore::core::roi::*;
+    use cvlcore::core::scale::*;
     use cvlcore::core::statistic::*;
+    use cvlcore::errors::ProcessingError;
     use cvlcore::*;
-    use opencv::core::{Mat, MatTraitConst};
+    use opencv::core::{absdiff, count_non_zero, mean, Mat, MatTraitConst, MatTraitConstManual};
+    use opencv::core::{Point, Rect};
     use opencv::imgcodecs::imread;
     use std::path::Path;
     use std::rc::Rc;
@@ -49,11 +57,205 @@ mod main_test {
         let mat = frames.first().unwrap();
         let cvlmat = CvlMat::new(mat.clone());
         let gray = gen_grayscale_frame(&cvlmat).unwrap();
-        let canny = gen_canny_frame_by_sigma(&gray, 3, 0.05, true).unwrap();
+        let canny = gen_canny_frame_by_mean_legacy(&gray, 3, 0.05, true).unwrap();
         assert_eq!(canny.frame().channels(), 1);
         assert_eq!(canny.frame().dims(), 2);
     }
 
+    #[test]
+    fn test_gradient_frames() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+
+        let gradients = [
+            gen_sobel_magnitude_frame(&gray, 3).unwrap(),
+            gen_scharr_frame(&gray).unwrap(),
+            gen_laplacian_frame(&gray, 3).unwrap(),
+        ];
+
+        for gradient in gradients.iter() {
+            assert_eq!(gradient.frame().channels(), 1);
+            assert_eq!(gradient.frame().depth(), opencv::core::CV_8U);
+            assert_eq!(gradient.rows(), gray.rows());
+        }
+
+        let err = gen_sobel_magnitude_frame(&gray, 4).unwrap_err();
+        assert!(matches!(
+            err,
+            ProcessingError::OpenCv {
+                operation: "sobel",
+                ..
+            }
+        ));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_denoise() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+
+        let methods = [
+            Denoise::None,
+            Denoise::Gaussian {
+                ksize: 5,
+                sigma: 1.5,
+            },
+            Denoise::Bilateral {
+                diameter: 5,
+                sigma_color: 50.0,
+                sigma_space: 50.0,
+            },
+            Denoise::Median { ksize: 3 },
+        ];
+
+        for method in methods.iter() {
+            let denoised = gen_denoised_frame(&gray, method).unwrap();
+            assert_eq!(denoised.frame().channels(), 1);
+            assert_eq!(denoised.rows(), gray.rows());
+        }
+
+        assert!(gen_denoised_frame(&gray, &Denoise::Median { ksize: 4 }).is_err());
+    }
+
+    #[test]
+    fn test_temporal_median() {
+        let frames = [[10u8, 200, 30], [20, 0, 30], [250, 100, 30]]
+            .iter()
+            .map(|data| Mat::from_slice_2d(&[&data[..]]).unwrap())
+            .map(|mat| Rc::new(CvlMat::new(mat)))
+            .collect::<Vec<Rc<CvlMat>>>();
+
+        let median = gen_temporal_median_frame(&frames).unwrap();
+        let values = median.frame().data_typed::<u8>().unwrap().to_vec();
+        assert_eq!(values, vec![20, 100, 30]);
+        assert!(gen_temporal_median_frame(&[]).is_err());
+    }
+
+    #[test]
+    fn test_resize() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+
+        let (rows, cols) =
+            Resize::Width(cvlmat.columns() / 2).target_size(cvlmat.rows(), cvlmat.columns());
+        let resized = gen_resized_frame(&cvlmat, rows, cols).unwrap();
+        assert_eq!(resized.columns(), cvlmat.columns() / 2);
+        assert_eq!(resized.rows(), rows);
+        assert_eq!(Resize::Scale(0.5).target_size(100, 200), (50, 100));
+        assert_eq!(Resize::None.target_size(100, 200), (100, 200));
+    }
+
+    #[test]
+    fn test_resolution_scale() {
+        let scale = ResolutionScale::new(1920, 640);
+        assert_eq!(scale.factor(), 3.0);
+        assert_eq!(scale.scale_length(2), 6);
+        assert_eq!(scale.scale_bounds(&ColorBounds::default()).get(1), 24);
+
+        let stat = Statistic {
+            area: 1920 * 1080,
+            ..Statistic::new(90, 180, 9, 0)
+        };
+        let normalized = scale.normalize_statistic(&stat);
+        assert_eq!(
+            (normalized.ch1, normalized.ch2, normalized.ch3),
+            (10, 20, 1)
+        );
+        assert_eq!(normalized.area, 640 * 360);
+        assert_eq!(normalized.density(), stat.density());
+
+        let disabled = ResolutionScale::new(1920, 0);
+        assert_eq!(disabled.scale_length(8), 8);
+        assert_eq!(ResolutionScale::new(320, 640).scale_length(1), 1);
+    }
+
+    #[test]
+    fn test_illumination() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+
+        let methods = [
+            Illumination::None,
+            Illumination::Histogram,
+            Illumination::Clahe {
+                clip_limit: 2.0,
+                tile_size: 8,
+            },
+            Illumination::MeanStd {
+                mean: 128.0,
+                std: 40.0,
+            },
+        ];
+
+        for method in methods.iter() {
+            let normalized = gen_illumination_frame(&gray, method).unwrap();
+            assert_eq!(normalized.frame().channels(), 1);
+            assert_eq!(normalized.rows(), gray.rows());
+        }
+
+        let method = Illumination::MeanStd {
+            mean: 100.0,
+            std: 20.0,
+        };
+        let normalized = gen_illumination_frame(&gray, &method).unwrap();
+        let mean = calculate_mat_mean(&normalized).unwrap();
+        assert!((mean - 100.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_flicker_compensation() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+
+        let mut steady = FlickerCompensator::new(6, 0.01);
+        for _ in 0..6 {
+            let (_, is_compensated) = steady.process(&gray).unwrap();
+            assert!(!is_compensated);
+        }
+
+        let mut compensator = FlickerCompensator::new(6, 0.01);
+        let mut flags = Vec::new();
+        for index in 0..12 {
+            let gain = if index % 2 == 0 { 0.8 } else { 1.2 };
+            let mut scaled = Mat::default();
+            gray.frame().convert_to(&mut scaled, -1, gain, 0.0).unwrap();
+            let (_, is_compensated) = compensator.process(&CvlMat::new(scaled)).unwrap();
+            flags.push(is_compensated);
+        }
+
+        assert!(compensator.is_flickering());
+        assert!(flags.iter().skip(6).all(|flag| *flag));
+    }
+
+    #[test]
+    fn test_flicker_noise_is_not_compensated() {
+        let mut compensator = FlickerCompensator::new(6, 0.01);
+        let mut state: u64 = 7;
+        for _ in 0..48 {
+            state = (state * 1103515245 + 12345) % (1 << 31);
+            let intensity = 100.0 + ((state >> 16) % 7) as f64 - 3.0;
+            let frame = Mat::new_rows_cols_with_default(
+                16,
+                16,
+                opencv::core::CV_8UC1,
+                opencv::core::Scalar::all(intensity),
+            )
+            .unwrap();
+            let (_, is_compensated) = compensator.process(&CvlMat::new(frame)).unwrap();
+            assert!(!is_compensated);
+        }
+    }
+
     #[test]
     fn test_distribution() {
         let frames = load_resource_frames();
@@ -65,14 +267,40 @@ mod main_test {
         // assert_eq!(distrib.frame().dims(), 2);
     }
 
+    #[test]
+    fn test_compute_mean() {
+        let frames = load_resource_frames();
+        let mat = frames.first().unwrap();
+        let cvlmat = CvlMat::new(mat.clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+        let mean = calculate_mat_mean(&gray).unwrap_or(0f64);
+        assert_eq!(mean, 194.86283854166666);
+    }
+
     #[test]
     fn test_compute_median() {
+        let data: [u8; 8] = [0, 10, 10, 20, 30, 200, 250, 255];
+        let mat = Mat::from_slice_2d(&[&data[..]]).unwrap();
+        let cvlmat = CvlMat::new(mat);
+
+        assert_eq!(calculate_mat_median(&cvlmat), Some(20.0));
+        assert_eq!(calculate_mat_percentile(&cvlmat, 0.0), Some(0.0));
+        assert_eq!(calculate_mat_percentile(&cvlmat, 25.0), Some(10.0));
+        assert_eq!(calculate_mat_percentile(&cvlmat, 100.0), Some(255.0));
+        assert_eq!(calculate_mat_percentile(&cvlmat, 101.0), None);
+        assert_eq!(calculate_mat_median(&CvlMat::default()), None);
+    }
+
+    #[test]
+    fn test_auto_canny() {
         let frames = load_resource_frames();
         let mat = frames.first().unwrap();
         let cvlmat = CvlMat::new(mat.clone());
         let gray = gen_grayscale_frame(&cvlmat).unwrap();
-        let median = calculate_mat_median(&gray).unwrap_or(0f64);
-        assert_eq!(median, 194.86283854166666);
+        let canny = gen_auto_canny_frame(&gray, 3, 0.33, true).unwrap();
+        assert_eq!(canny.frame().channels(), 1);
+        assert_eq!(canny.rows(), gray.rows());
+        assert!(gen_auto_canny_frame(&cvlmat, 3, 0.33, true).is_err());
     }
 
     #[test]
@@ -81,7 +309,7 @@ mod main_test {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -96,7 +324,7 @@ mod main_test {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -111,15 +339,15 @@ mod main_test {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
         let abs_frame = gen_abs_frame(&frames).unwrap();
         let abs_frame_reduce = gen_abs_frame_reduce(&frames).unwrap();
 
-        let abs_mean = calculate_mat_median(&abs_frame).unwrap();
-        let abs_mean_reduce = calculate_mat_median(&abs_frame_reduce).unwrap();
+        let abs_mean = calculate_mat_mean(&abs_frame).unwrap();
+        let abs_mean_reduce = calculate_mat_mean(&abs_frame_reduce).unwrap();
 
         println!("ABS: {}\nABS (Reduce): {}\n", abs_mean, abs_mean_reduce);
         assert_ne!(abs_mean, abs_mean_reduce);
@@ -131,7 +359,7 @@ mod main_test {
             .into_iter()
             .map(CvlMat::new)
             .map(|m| gen_grayscale_frame(&m).unwrap())
-            .map(|m| gen_canny_frame_by_sigma(&m, 3, 0.05, true).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
             .map(Rc::new)
             .collect::<Vec<Rc<CvlMat>>>();
 
@@ -142,6 +370,131 @@ mod main_test {
         assert_eq!(result.frame().dims(), 2);
     }
 
+    #[test]
+    fn test_amplitude() {
+        let frames = load_resource_frames()
+            .into_iter()
+            .map(CvlMat::new)
+            .map(|m| gen_grayscale_frame(&m).unwrap())
+            .map(Rc::new)
+            .collect::<Vec<Rc<CvlMat>>>();
+
+        let amplitude = gen_amplitude_frame(&frames).unwrap();
+        assert_eq!(amplitude.frame().channels(), 1);
+        assert_eq!(amplitude.frame().typ(), opencv::core::CV_32F);
+
+        let accumulated = gen_accumulated_amplitude_frame(&amplitude, &amplitude, 0.5).unwrap();
+        assert_eq!(accumulated.rows(), amplitude.rows());
+        assert_eq!(accumulated.columns(), amplitude.columns());
+
+        let colored = gen_colored_amplitude_frame(&accumulated, 2).unwrap();
+        assert_eq!(colored.frame().channels(), 3);
+        assert!(matches!(
+            gen_amplitude_frame(&frames[0..1]),
+            Err(ProcessingError::NotEnoughFrames {
+                required: 2,
+                available: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_compute_vibrating_masked() {
+        let frames = load_resource_frames()
+            .into_iter()
+            .map(CvlMat::new)
+            .map(|m| gen_grayscale_frame(&m).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
+            .map(Rc::new)
+            .collect::<Vec<Rc<CvlMat>>>();
+
+        let abs_frame = gen_abs_frame_reduce(&frames).unwrap();
+        let rect = Rect::new(10, 10, 100, 50);
+        let roi = RegionOfInterest::Rectangles(vec![rect]);
+        let mask = roi.mask(abs_frame.rows(), abs_frame.columns()).unwrap();
+
+        let color_bounds = ColorBounds::default();
+        let full = compute_vibration(&abs_frame, 8, 2, &color_bounds).unwrap();
+        let masked = compute_vibration_masked(&abs_frame, &mask, 8, 2, &color_bounds).unwrap();
+
+        let full_stat = full.statistic().unwrap();
+        let masked_stat = masked.statistic().unwrap();
+        assert_eq!(masked_stat.area, 100 * 50);
+        assert_eq!(
+            full_stat.area,
+            (abs_frame.rows() * abs_frame.columns()) as u32
+        );
+        assert!(masked_stat.ch1 <= full_stat.ch1);
+        assert!(masked_stat.ch4 <= full_stat.ch4);
+    }
+
+    #[test]
+    fn test_roi_polygon_mask() {
+        let points = vec![Point::new(0, 0), Point::new(9, 0), Point::new(0, 9)];
+        let roi = RegionOfInterest::Polygons(vec![points]);
+        let mask = roi.mask(20, 20).unwrap();
+        assert_eq!(mask.rows(), 20);
+        assert!(count_non_zero(mask.frame()).unwrap() > 0);
+
+        let wrong_size = RegionOfInterest::Mask(mask);
+        assert!(wrong_size.mask(10, 10).is_err());
+    }
+
+    #[test]
+    fn test_density_statistic() {
+        let mut stat_1 = Statistic::new(354, 256, 129, 80);
+        let mut stat_2 = Statistic::new(879, 567, 280, 143);
+        stat_1.area = 10000;
+        stat_2.area = 20000;
+
+        assert_eq!(stat_1.density()[0], 3.54);
+        assert_eq!(Statistic::new(1, 1, 1, 1).density(), [0f32; 4]);
+
+        let dispersion = compute_density_statistic(vec![&stat_1, &stat_2], 10.0);
+        assert!(dispersion.ch1 > 0f32);
+
+        let mut stat_3 = Statistic::new(708, 512, 258, 160);
+        stat_3.area = 20000;
+        let same_density = compute_density_statistic(vec![&stat_1, &stat_3], 10.0);
+        let repeated = compute_density_statistic(vec![&stat_1, &stat_1], 10.0);
+        assert_eq!(same_density.ch1, repeated.ch1);
+        assert_eq!(same_density.ch4, repeated.ch4);
+    }
+
+    #[test]
+    fn test_frame_motion() {
+        let frames = load_resource_frames();
+        let cvlmat = CvlMat::new(frames.first().unwrap().clone());
+        let gray = gen_grayscale_frame(&cvlmat).unwrap();
+
+        let shift = Motion::new(5.0, -3.0, 0.0, 0.0);
+        let shifted = gen_stabilized_frame(&gray, &shift).unwrap();
+        assert_eq!(shifted.rows(), gray.rows());
+
+        // The warp samples source frame at shifted coordinates, so frame content moves by
+        // (-5, 3) pixels and this motion is estimated relative to reference frame.
+        let method = Stabilization::PhaseCorrelation;
+        let motion = estimate_frame_motion(&gray, &shifted, method, None).unwrap();
+        assert!((motion.dx + 5.0).abs() < 0.5);
+        assert!((motion.dy - 3.0).abs() < 0.5);
+
+        let region = Some(Rect::new(20, 20, 200, 200));
+        let motion = estimate_frame_motion(&gray, &shifted, method, region).unwrap();
+        assert!((motion.dx + 5.0).abs() < 0.5);
+        assert!((motion.dy - 3.0).abs() < 0.5);
+
+        let restored = gen_stabilized_frame(&shifted, &motion).unwrap();
+        assert_eq!(restored.columns(), gray.columns());
+
+        let interior = Rect::new(20, 20, gray.columns() - 40, gray.rows() - 40);
+        let restored_roi = Mat::roi(restored.frame(), interior).unwrap();
+        let gray_roi = Mat::roi(gray.frame(), interior).unwrap();
+        let mut diff = Mat::default();
+        absdiff(&restored_roi, &gray_roi, &mut diff).unwrap();
+        let mean_diff = mean(&diff, &Mat::default()).unwrap()[0];
+        assert!(mean_diff < 3.0, "mean difference {} is too big", mean_diff);
+    }
+
     #[test]
     fn test_chain_statistic() {
         let stat_1 = Statistic::new(354, 256, 129, 80);
@@ -158,6 +511,69 @@ mod main_test {
         assert_eq!(dispersion.ch4, 15.147937);
     }
 
+    #[test]
+    fn test_bad_mats() {
+        let frames = load_resource_frames();
+        let color = CvlMat::new(frames[0].clone());
+        let gray = gen_grayscale_frame(&color).unwrap();
+
+        let mut float = Mat::default();
+        gray.frame()
+            .convert_to(&mut float, opencv::core::CV_32F, 1.0, 0.0)
+            .unwrap();
+        let float = CvlMat::new(float);
+
+        let roi = Mat::roi(gray.frame(), Rect::new(10, 10, 100, 50)).unwrap();
+        let continuous = CvlMat::new(roi.try_clone().unwrap());
+        let sliced = CvlMat::new(roi);
+        assert!(!sliced.frame().is_continuous());
+
+        let bounds = ColorBounds::default();
+        for mat in [&CvlMat::default(), &color, &float, &sliced] {
+            let _ = gen_grayscale_frame(mat);
+            let _ = gen_threshold_frame(mat, 127.0, 255.0);
+            let _ = gen_canny_frame_by_mean_legacy(mat, 3, 0.05, true);
+            let _ = gen_auto_canny_frame(mat, 3, 0.33, true);
+            let _ = gen_distribution_frame(mat, 100.0, 255.0);
+            let _ = gen_sobel_magnitude_frame(mat, 3);
+            let _ = gen_denoised_frame(mat, &Denoise::Median { ksize: 3 });
+            let _ = gen_temporal_median_frame(&[Rc::new(mat.clone()), Rc::new(mat.clone())]);
+            let _ = gen_abs_frame(&[Rc::new(mat.clone()), Rc::new(gray.clone())]);
+            let _ = gen_abs_frame_reduce(&[Rc::new(mat.clone()), Rc::new(gray.clone())]);
+            let _ = gen_amplitude_frame(&[Rc::new(mat.clone()), Rc::new(mat.clone())]);
+            let _ = compute_neighbours_map(mat, 8, 2);
+            let _ = compute_vibration(mat, 8, 2, &bounds);
+            let _ = mat.to_f64_vec();
+        }
+
+        for mat in [&CvlMat::default(), &color, &float] {
+            assert_eq!(calculate_mat_mean(mat), None);
+            assert_eq!(calculate_mat_median(mat), None);
+            assert!(gen_canny_frame_by_mean_legacy(mat, 3, 0.05, true).is_err());
+        }
+
+        assert_eq!(calculate_mat_mean(&sliced), calculate_mat_mean(&continuous));
+        assert_eq!(
+            calculate_mat_median(&sliced),
+            calculate_mat_median(&continuous)
+        );
+
+        assert!(compute_vibration(&color, 8, 2, &bounds).is_err());
+        assert!(matches!(
+            gen_abs_frame(&[]),
+            Err(ProcessingError::NotEnoughFrames { .. })
+        ));
+        assert!(matches!(
+            gen_abs_frame_reduce(&[]),
+            Err(ProcessingError::NotEnoughFrames { .. })
+        ));
+
+        let stats = [Statistic::new(u16::MAX, u16::MAX, 1, 0)];
+        let _ = compute_statistic(stats.iter().collect(), 1.0);
+        assert!(Dispersion::try_from(vec![1.0, 2.0]).is_err());
+        assert!(Dispersion::try_from(vec![1.0, 2.0, 3.0, 4.0]).is_ok());
+    }
+
     fn load_resource_frames() -> Vec<Mat> {
         let flags = 3;
         Path::new("test/resources/")
@@ -0,0 +1,108 @@
+extern crate cvlcore;
+
+#[cfg(test)]
+mod render_test {
+    use cvlcore::core::bounds::ColorBounds;
+    use cvlcore::core::mat::CvlMat;
+    use cvlcore::ui::render::*;
+    use cvlcore::*;
+    use opencv::core::{Mat, MatTraitConst, CV_8UC3};
+    use opencv::imgcodecs::imread;
+    use std::path::Path;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_render_neighbours_map() {
+        let abs_frame = load_abs_frame();
+        let neighbours_map = compute_neighbours_map(&abs_frame, 8, 2).unwrap();
+        assert_eq!(neighbours_map.frame().channels(), 1);
+
+        let rendered = render_map(&neighbours_map, &Colormap::Turbo, 16.0).unwrap();
+        assert_eq!(rendered.typ(), CV_8UC3);
+        assert_eq!(rendered.rows(), abs_frame.rows());
+        assert_eq!(rendered.columns(), abs_frame.columns());
+    }
+
+    #[test]
+    fn test_render_custom_colormap() {
+        let abs_frame = load_abs_frame();
+        let neighbours_map = compute_neighbours_map(&abs_frame, 8, 2).unwrap();
+
+        let lut = (0..=255u8)
+            .map(|v| [v, 0, 255 - v])
+            .collect::<Vec<[u8; 3]>>();
+        let rendered = render_map(&neighbours_map, &Colormap::Custom(lut), 16.0).unwrap();
+        assert_eq!(rendered.typ(), CV_8UC3);
+
+        let short_lut = Colormap::Custom(vec![[0, 0, 0]; 16]);
+        assert!(render_map(&neighbours_map, &short_lut, 16.0).is_err());
+    }
+
+    #[test]
+    fn test_render_vibration() {
+        let abs_frame = load_abs_frame();
+        let color_bounds = ColorBounds::default();
+        let vibration = compute_vibration(&abs_frame, 8, 2, &color_bounds).unwrap();
+        let rendered = render_vibration(&vibration).unwrap();
+        assert_eq!(rendered.typ(), CV_8UC3);
+    }
+
+    #[test]
+    fn test_render_colorbar() {
+        let abs_frame = load_abs_frame();
+        let neighbours_map = compute_neighbours_map(&abs_frame, 8, 2).unwrap();
+        let rendered = render_map(&neighbours_map, &Colormap::Inferno, 16.0).unwrap();
+
+        let bounds = ColorBounds::default();
+        let colorbar = render_colorbar(&Colormap::Inferno, &bounds, 16.0, rendered.rows()).unwrap();
+        assert_eq!(colorbar.rows(), rendered.rows());
+
+        let result = attach_colorbar(&rendered, &colorbar).unwrap();
+        assert_eq!(result.columns(), rendered.columns() + colorbar.columns());
+    }
+
+    #[test]
+    fn test_render_overlay() {
+        let frames = load_resource_frames();
+        let source = CvlMat::new(frames.first().unwrap().clone());
+
+        let abs_frame = load_abs_frame();
+        let color_bounds = ColorBounds::default();
+        let vibration = compute_vibration(&abs_frame, 8, 2, &color_bounds).unwrap();
+
+        let blended = render_overlay(&source, &vibration, 0.5, true).unwrap();
+        assert_eq!(blended.typ(), CV_8UC3);
+        assert_eq!(blended.rows(), source.rows());
+
+        let gray = gen_grayscale_frame(&source).unwrap();
+        let blended = render_overlay(&gray, &vibration, 1.0, false).unwrap();
+        assert_eq!(blended.typ(), CV_8UC3);
+
+        let other_size = CvlMat::default();
+        assert!(render_overlay(&other_size, &vibration, 0.5, true).is_err());
+    }
+
+    fn load_abs_frame() -> CvlMat {
+        let frames = load_resource_frames()
+            .into_iter()
+            .map(CvlMat::new)
+            .map(|m| gen_grayscale_frame(&m).unwrap())
+            .map(|m| gen_canny_frame_by_mean_legacy(&m, 3, 0.05, true).unwrap())
+            .map(Rc::new)
+            .collect::<Vec<Rc<CvlMat>>>();
+
+        gen_abs_frame_reduce(&frames).unwrap()
+    }
+
+    fn load_resource_frames() -> Vec<Mat> {
+        let flags = 3;
+        Path::new("test/resources/")
+            .read_dir()
+            .unwrap()
+            .map(Result::unwrap)
+            .filter(|f| f.file_name().to_str().unwrap().contains("test_file_"))
+            .map(|f| f.path().to_str().unwrap().to_string())
+            .map(|f| imread(f.as_str(), flags).unwrap())
+            .collect()
+    }
+}